@@ -0,0 +1,622 @@
+use std::{collections::HashMap, fmt, sync::Mutex};
+
+use action::github::{GitHub, PullRequest, PullRequestDetails, PullRequestPayload};
+use anyhow::anyhow;
+use async_trait::async_trait;
+use config::types::{Commands, Repo};
+use pr::prefix::Backport;
+use reconcile::{error::Result, resource::Resource};
+use serde::{Deserialize, Serialize};
+use slash::command::{commands, Command};
+use version::branch::Branch;
+
+use crate::git::{Git, Picked};
+
+// LABEL_PREFIX marks the release branches a PR is backported to, e.g.
+// `cherry-pick/release-0.2`
+const LABEL_PREFIX: &str = "cherry-pick/";
+
+// conflict_marker ends the line of a conflict in the outcome comment, the
+// branch is not retried once the comment holds it.
+fn conflict_marker(branch: &str) -> String {
+    format!("<!-- backport conflict: {branch} -->")
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum Why {
+    // Branch, Head
+    Missing(String, String),
+}
+
+impl fmt::Display for Why {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Why::Missing(branch, head) => {
+                write!(
+                    f,
+                    "cherry-pick to {:?} and open a PR from {:?}",
+                    branch, head
+                )
+            }
+        }
+    }
+}
+
+// Target is a branch the PR is backported to.
+#[derive(Serialize, Deserialize, PartialEq)]
+pub struct Target {
+    branch: String,
+    exists: bool,
+    // Backport is the PR already made to the branch
+    backport: Option<PullRequest>,
+    // Conflicted is true once a cherry-pick to the branch conflicted, it is
+    // backported by hand
+    conflicted: bool,
+}
+
+// Outcome is the result of a backport, it is commented on the original PR.
+pub enum Outcome {
+    // Branch, Backport
+    Opened(String, PullRequest),
+    // Branch, Files
+    Conflict(String, Vec<String>),
+}
+
+// Backports cherry-picks a merged PR to release branches and opens a PR per
+// branch, titled `:seedling: [release-0.2] <original title>`.
+pub struct Backports<'a> {
+    pub client: &'a dyn GitHub,
+    pub git: &'a Git,
+    pub pull_request: &'a PullRequestDetails,
+    pub targets: Vec<String>,
+
+    // Bot is the login the outcome comments are made with
+    pub bot: &'a str,
+
+    // Applied records the outcomes, for the comment
+    pub applied: Mutex<Vec<Outcome>>,
+}
+
+// targets returns the branches a PR asks to be backported to, from its
+// `cherry-pick/*` labels and the `/cherry-pick` comments of users allowed to
+// run the command.
+pub async fn targets(
+    client: &dyn GitHub,
+    commands_config: &Commands,
+    org: &str,
+    repo: &str,
+    pull_request: &PullRequestDetails,
+) -> Result<Vec<String>> {
+    let mut targets: Vec<String> = pull_request
+        .labels
+        .iter()
+        .filter_map(|label| label.strip_prefix(LABEL_PREFIX))
+        .map(|branch| branch.to_string())
+        .collect();
+
+    let permission = commands_config.permission("cherry-pick");
+    let mut roles: HashMap<String, String> = HashMap::new();
+    for comment in client.list_comments(org, repo, pull_request.number).await? {
+        let (found, _) = commands(&comment.body, &[]);
+        let branches: Vec<String> = found
+            .into_iter()
+            .filter_map(|command| match command {
+                Command::CherryPick(branch) => Some(branch),
+                _ => None,
+            })
+            .collect();
+        if branches.is_empty() {
+            continue;
+        }
+
+        let Some(author) = comment.author else {
+            continue;
+        };
+        if !roles.contains_key(&author) {
+            let role = client.get_permission(org, repo, &author).await?;
+            roles.insert(author.clone(), role);
+        }
+        let is_author = pull_request.author.as_deref() == Some(author.as_str());
+        if permission.allows(&roles[&author], is_author) {
+            targets.extend(branches);
+        }
+    }
+
+    let mut unique: Vec<String> = vec![];
+    for target in targets {
+        if !unique.contains(&target) {
+            unique.push(target);
+        }
+    }
+    Ok(unique)
+}
+
+fn is_release_branch(branch: &str) -> bool {
+    matches!(branch.parse::<Branch>(), Ok(Branch::Release { .. }))
+}
+
+impl Backports<'_> {
+    // head is the branch the backport to `branch` is made from
+    fn head(&self, branch: &str) -> String {
+        format!("backport-{}-to-{}", self.pull_request.number, branch)
+    }
+
+    // comment reports the outcome of every backport in markdown
+    pub fn comment(&self) -> Option<String> {
+        let applied = self.applied.lock().unwrap();
+        if applied.is_empty() {
+            return None;
+        }
+
+        let sha = self.pull_request.merge_commit_sha.as_deref().unwrap_or("");
+        let mut comment = String::from("### Backports\n\n");
+        for outcome in applied.iter() {
+            match outcome {
+                Outcome::Opened(branch, backport) => {
+                    comment.push_str(&format!(
+                        "- :white_check_mark: `{}`: #{}\n",
+                        branch, backport.number
+                    ));
+                }
+                Outcome::Conflict(branch, files) => {
+                    let files: Vec<String> = files.iter().map(|f| format!("`{f}`")).collect();
+                    comment.push_str(&format!(
+                        "- :x: `{}`: cherry-picking {} conflicts in {}, please backport it by hand {}\n",
+                        branch,
+                        sha,
+                        files.join(", "),
+                        conflict_marker(branch)
+                    ));
+                }
+            }
+        }
+        Some(comment)
+    }
+}
+
+#[async_trait]
+impl Resource for Backports<'_> {
+    const KIND: &'static str = "Backport";
+
+    // Branch to backport to
+    type Desired = String;
+    type Observed = Vec<Target>;
+    type Change = Why;
+
+    fn desired(&self, _repo: &Repo) -> Vec<String> {
+        self.targets.clone()
+    }
+
+    async fn observe(&self, org: &str, repo: &str) -> Result<Vec<Target>> {
+        let outcomes: Vec<String> = self
+            .client
+            .list_comments(org, repo, self.pull_request.number)
+            .await?
+            .into_iter()
+            .filter(|comment| {
+                comment
+                    .author
+                    .as_deref()
+                    .is_some_and(|author| author.eq_ignore_ascii_case(self.bot))
+            })
+            .map(|comment| comment.body)
+            .collect();
+
+        let mut observed: Vec<Target> = vec![];
+        for branch in &self.targets {
+            if !is_release_branch(branch) {
+                continue;
+            }
+
+            let exists = self
+                .client
+                .get_ref(org, repo, &format!("heads/{}", branch))
+                .await?
+                .is_some();
+            let backport = self
+                .client
+                .find_pull_request(org, repo, &self.head(branch))
+                .await?;
+
+            let conflicted = outcomes
+                .iter()
+                .any(|body| body.contains(&conflict_marker(branch)));
+
+            observed.push(Target {
+                branch: branch.clone(),
+                exists,
+                backport,
+                conflicted,
+            });
+        }
+        Ok(observed)
+    }
+
+    fn diff(&self, _desired: &[String], observed: &Vec<Target>) -> Vec<Why> {
+        if !self.pull_request.merged {
+            return vec![];
+        }
+
+        observed
+            .iter()
+            .filter(|target| target.exists && target.backport.is_none() && !target.conflicted)
+            .map(|target| Why::Missing(target.branch.clone(), self.head(&target.branch)))
+            .collect()
+    }
+
+    fn warnings(&self, desired: &[String], observed: &Vec<Target>) -> Vec<String> {
+        let mut warnings: Vec<String> = vec![];
+
+        if !self.pull_request.merged {
+            warnings.push(format!(
+                "#{} is not merged, it is backported once merged",
+                self.pull_request.number
+            ));
+        }
+        for branch in desired {
+            if !is_release_branch(branch) {
+                warnings.push(format!(
+                    "{:?} is not a release branch, expected release-X.Y",
+                    branch
+                ));
+            }
+        }
+        for target in observed {
+            if !target.exists {
+                warnings.push(format!("branch {:?} does not exist", target.branch));
+            }
+            if target.conflicted && target.backport.is_none() {
+                warnings.push(format!(
+                    "cherry-picking to {:?} conflicted, it is left to be backported by hand",
+                    target.branch
+                ));
+            }
+        }
+
+        warnings
+    }
+
+    async fn apply(&self, org: &str, repo: &str, change: &Why) -> Result<()> {
+        let Why::Missing(branch, head) = change;
+
+        let sha = self
+            .pull_request
+            .merge_commit_sha
+            .as_deref()
+            .ok_or_else(|| anyhow!("#{} has no merge commit", self.pull_request.number))?;
+
+        // A rebase merged PR is every one of its commits, not only the last
+        let commits = self
+            .git
+            .commits(sha, &self.pull_request.head_sha, self.pull_request.commits)
+            .map_err(|error| anyhow!(error))?;
+        let picked = self
+            .git
+            .cherry_pick(branch, head, &commits)
+            .map_err(|error| anyhow!(error))?;
+        let outcome = match picked {
+            Picked::Clean => {
+                self.git.push(head).map_err(|error| anyhow!(error))?;
+
+                let title = Backport {
+                    branch: branch.clone(),
+                    title: self.pull_request.title.clone(),
+                };
+                let resp = self
+                    .client
+                    .create_pull_request(
+                        org,
+                        repo,
+                        &PullRequestPayload {
+                            title: title.to_string(),
+                            head: head.clone(),
+                            base: branch.clone(),
+                            body: format!(
+                                "Backport of #{} to `{}`, cherry-picked from {}.",
+                                self.pull_request.number,
+                                branch,
+                                commits.join(", ")
+                            ),
+                        },
+                    )
+                    .await?;
+                eprintln!("Backport opened: {:?}", resp);
+                Outcome::Opened(branch.clone(), resp)
+            }
+            Picked::Conflict(files) => {
+                eprintln!("Backport to {:?} conflicts in {:?}", branch, files);
+                Outcome::Conflict(branch.clone(), files)
+            }
+        };
+
+        self.applied.lock().unwrap().push(outcome);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, fs, path::Path, process::Command, sync::Mutex};
+
+    use action::{
+        fake::{FakeGitHub, FakeRepo},
+        github::{Comment, GitHub, PullRequestDetails, Reference},
+    };
+    use config::types::Commands;
+    use reconcile::{
+        plan::Plan,
+        testing::{apply_and_recompute, render, repos},
+    };
+    use tempfile::TempDir;
+
+    use crate::{
+        backport::{targets, Backports},
+        git::Git,
+    };
+
+    const BOT: &str = "github-actions[bot]";
+
+    fn git(path: &Path, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(path)
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?}: {:?}", args, output);
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    fn commit(path: &Path, file: &str, content: &str) -> String {
+        fs::write(path.join(file), content).unwrap();
+        git(path, &["add", file]);
+        git(path, &["commit", "-m", &format!("Change {file}")]);
+        git(path, &["rev-parse", "HEAD"])
+    }
+
+    // origin has a release-0.2 branch and two more commits on main, the last
+    // one conflicts with release-0.3
+    fn origin() -> (TempDir, String) {
+        let origin = TempDir::new().unwrap();
+        let path = origin.path();
+        git(path, &["init", "-b", "main"]);
+        commit(path, "a.txt", "a\n");
+        git(path, &["branch", "release-0.2"]);
+        commit(path, "b.txt", "b\n");
+        git(path, &["branch", "release-0.3"]);
+        git(path, &["checkout", "release-0.3"]);
+        commit(path, "c.txt", "release\n");
+        git(path, &["checkout", "main"]);
+        let sha = commit(path, "c.txt", "main\n");
+        (origin, sha)
+    }
+
+    fn pull_request(sha: &str) -> PullRequestDetails {
+        PullRequestDetails {
+            number: 12,
+            title: ":bug: Fix bug".to_string(),
+            author: Some("author".to_string()),
+            base: "main".to_string(),
+            head: "fix".to_string(),
+            head_sha: sha.to_string(),
+            commits: 1,
+            merged: true,
+            merge_commit_sha: Some(sha.to_string()),
+            labels: vec![
+                "kind/bug".to_string(),
+                "cherry-pick/release-0.2".to_string(),
+                "cherry-pick/main".to_string(),
+            ],
+        }
+    }
+
+    fn comment(id: u64, author: &str, body: &str) -> Comment {
+        Comment {
+            id,
+            body: body.to_string(),
+            author: Some(author.to_string()),
+        }
+    }
+
+    // clone returns a clone of origin the backport branches are made in
+    fn clone(origin: &Path) -> (TempDir, Git) {
+        let clone = TempDir::new().unwrap();
+        git(clone.path(), &["clone", origin.to_str().unwrap(), "."]);
+        git(clone.path(), &["config", "user.name", "test"]);
+        git(clone.path(), &["config", "user.email", "test@example.com"]);
+        let git = Git {
+            path: clone.path().to_str().unwrap().to_string(),
+            remote: "origin".to_string(),
+        };
+        (clone, git)
+    }
+
+    // client knows the release branches of the repository and the comments of
+    // the PR
+    fn client(branches: &[&str], comments: Vec<Comment>) -> FakeGitHub {
+        FakeGitHub::new().with_repo(
+            "org",
+            "repo",
+            FakeRepo {
+                comments: HashMap::from([(12, comments)]),
+                refs: branches
+                    .iter()
+                    .map(|branch| Reference {
+                        name: format!("refs/heads/{branch}"),
+                        sha: "".to_string(),
+                    })
+                    .collect(),
+                ..Default::default()
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn from_labels_and_comments() {
+        let client = FakeGitHub::new().with_repo(
+            "org",
+            "repo",
+            FakeRepo {
+                comments: HashMap::from([(
+                    12,
+                    vec![
+                        comment(1, "maintainer", "/cherry-pick release-0.3 release-0.2"),
+                        comment(2, "someone", "/cherry-pick release-0.4"),
+                        comment(3, "author", "```\n/cherry-pick release-0.5\n```"),
+                    ],
+                )]),
+                permissions: HashMap::from([("maintainer".to_string(), "write".to_string())]),
+                ..Default::default()
+            },
+        );
+
+        let found = targets(
+            &client,
+            &Commands::default(),
+            "org",
+            "repo",
+            &pull_request("abc"),
+        )
+        .await
+        .unwrap();
+        assert_eq!(vec!["release-0.2", "main", "release-0.3"], found);
+    }
+
+    #[tokio::test]
+    async fn backport() {
+        let (origin, sha) = origin();
+        let (_clone, clone_git) = clone(origin.path());
+        // Only the conflicts the bot reported are skipped
+        let client = client(
+            &["release-0.2", "release-0.3"],
+            vec![comment(
+                1,
+                "someone",
+                "<!-- backport conflict: release-0.3 -->",
+            )],
+        );
+        let repos = repos(&["repo"]);
+
+        let pull_request = pull_request(&sha);
+        let backports = Backports {
+            client: &client,
+            git: &clone_git,
+            pull_request: &pull_request,
+            targets: vec![
+                "release-0.2".to_string(),
+                "release-0.3".to_string(),
+                "release-0.4".to_string(),
+                "main".to_string(),
+            ],
+            bot: BOT,
+            applied: Mutex::new(vec![]),
+        };
+
+        let plan = Plan::compute(&backports, &repos).await.unwrap();
+        assert_eq!(2, plan.warnings.len());
+        assert_eq!(
+            "\
+org/repo: cherry-pick to \"release-0.2\" and open a PR from \"backport-12-to-release-0.2\"
+org/repo: cherry-pick to \"release-0.3\" and open a PR from \"backport-12-to-release-0.3\"",
+            render(&backports, &repos).await
+        );
+
+        // Opened backports are not made again, the conflict is until it is
+        // commented
+        assert_eq!(
+            "org/repo: cherry-pick to \"release-0.3\" and open a PR from \"backport-12-to-release-0.3\"",
+            apply_and_recompute(&backports, &repos).await
+        );
+
+        // The clean cherry-pick is pushed and opened as a PR
+        assert_eq!(
+            "main",
+            git(origin.path(), &["show", "backport-12-to-release-0.2:c.txt"])
+        );
+        let repo = client.repo("org", "repo");
+        assert_eq!(1, repo.pull_requests.len());
+        assert_eq!(
+            ":seedling: [release-0.2] :bug: Fix bug",
+            repo.pull_requests[0].title
+        );
+        assert_eq!("release-0.2", repo.pull_requests[0].base);
+
+        let comment = backports.comment().unwrap();
+        assert_eq!(
+            format!(
+                "\
+### Backports
+
+- :white_check_mark: `release-0.2`: #1
+- :x: `release-0.3`: cherry-picking {sha} conflicts in `c.txt`, please backport it by hand <!-- backport conflict: release-0.3 -->
+"
+            ),
+            comment
+        );
+
+        // Once the bot reported it, the conflict is left to be done by hand
+        client
+            .create_comment("org", "repo", 12, &comment)
+            .await
+            .unwrap();
+        let plan = Plan::compute(&backports, &repos).await.unwrap();
+        assert!(plan.is_empty());
+        assert_eq!(3, plan.warnings.len());
+    }
+
+    // A rebase merged PR is backported with every one of its commits, a
+    // squashed one with its single commit
+    #[tokio::test]
+    async fn rebase_merged() {
+        let (origin, _) = origin();
+        let path = origin.path();
+        git(path, &["checkout", "-b", "fix", "release-0.2"]);
+        commit(path, "d.txt", "d\n");
+        let head_sha = commit(path, "e.txt", "e\n");
+        git(path, &["checkout", "main"]);
+        git(path, &["cherry-pick", "release-0.2..fix"]);
+        let sha = git(path, &["rev-parse", "HEAD"]);
+        git(path, &["checkout", "-b", "squashed", "main~2"]);
+        git(path, &["merge", "--squash", "fix"]);
+        git(path, &["commit", "-m", ":bug: Fix bug (#12)"]);
+        let squashed = git(path, &["rev-parse", "HEAD"]);
+        git(path, &["checkout", "main"]);
+
+        let (_clone, clone_git) = clone(path);
+        assert_eq!(
+            vec![squashed.clone()],
+            clone_git.commits(&squashed, &head_sha, 2).unwrap()
+        );
+
+        let client = client(&["release-0.2"], vec![]);
+        let repos = repos(&["repo"]);
+        let pull_request = PullRequestDetails {
+            head_sha,
+            commits: 2,
+            ..pull_request(&sha)
+        };
+        let backports = Backports {
+            client: &client,
+            git: &clone_git,
+            pull_request: &pull_request,
+            targets: vec!["release-0.2".to_string()],
+            bot: BOT,
+            applied: Mutex::new(vec![]),
+        };
+
+        assert_eq!("", apply_and_recompute(&backports, &repos).await);
+        assert_eq!(
+            "d\ne",
+            git(
+                path,
+                &[
+                    "show",
+                    "backport-12-to-release-0.2:d.txt",
+                    "backport-12-to-release-0.2:e.txt"
+                ]
+            )
+        );
+    }
+}
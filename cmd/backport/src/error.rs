@@ -0,0 +1,22 @@
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("{command} failed: {stderr}")]
+    Git { command: String, stderr: String },
+
+    #[error(transparent)]
+    Config(#[from] config::error::Error),
+
+    #[error(transparent)]
+    Action(#[from] action::error::Error),
+
+    #[error(transparent)]
+    Reconcile(#[from] reconcile::error::Error),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error(transparent)]
+    Any(#[from] anyhow::Error),
+}
@@ -0,0 +1,118 @@
+use std::process::{Command, Output};
+
+use crate::error::{Error, Result};
+
+// Git runs git in a clone of the repository, backport branches are made there
+// and pushed to `remote`.
+pub struct Git {
+    pub path: String,
+    pub remote: String,
+}
+
+// Picked is the outcome of a cherry-pick.
+#[derive(Debug, PartialEq)]
+pub enum Picked {
+    Clean,
+    // Files with conflicts, the cherry-pick is aborted
+    Conflict(Vec<String>),
+}
+
+impl Git {
+    fn git(&self, args: &[&str]) -> Result<Output> {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(&self.path)
+            .args(args)
+            .output()?;
+        Ok(output)
+    }
+
+    // run fails when git does
+    fn run(&self, args: &[&str]) -> Result<String> {
+        let output = self.git(args)?;
+        if !output.status.success() {
+            return Err(Error::Git {
+                command: format!("git {}", args.join(" ")),
+                stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            });
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    // commits returns the commits to cherry-pick for a PR merged as `sha`.
+    // A rebase merge replays the `count` commits of the PR on top of the base
+    // branch and `sha` is only the last of them, it is told apart from a
+    // squash by keeping the author, date and message of the PR's last commit,
+    // `pull_head`.
+    pub fn commits(&self, sha: &str, pull_head: &str, count: u64) -> Result<Vec<String>> {
+        self.run(&["fetch", &self.remote, sha])?;
+        if count < 2 || self.parents(sha)? > 1 {
+            return Ok(vec![sha.to_string()]);
+        }
+
+        self.run(&["fetch", &self.remote, pull_head])?;
+        let format = "--format=%an%n%ae%n%ad%n%B";
+        if self.run(&["show", "-s", format, sha])?
+            != self.run(&["show", "-s", format, pull_head])?
+        {
+            return Ok(vec![sha.to_string()]);
+        }
+
+        let commits = self.run(&[
+            "rev-list",
+            "--reverse",
+            "--first-parent",
+            &format!("--max-count={count}"),
+            sha,
+        ])?;
+        Ok(commits.lines().map(|commit| commit.to_string()).collect())
+    }
+
+    // parents returns the number of parents of the commit
+    fn parents(&self, sha: &str) -> Result<usize> {
+        let parents = self.run(&["rev-list", "--parents", "-n", "1", sha])?;
+        Ok(parents.split_whitespace().count() - 1)
+    }
+
+    // cherry_pick applies the commits on top of `branch` in a new local branch
+    // `head`. A merge commit is picked against its first parent.
+    pub fn cherry_pick(&self, branch: &str, head: &str, commits: &[String]) -> Result<Picked> {
+        let Some(last) = commits.last() else {
+            return Ok(Picked::Clean);
+        };
+        self.run(&["fetch", &self.remote, branch, last])?;
+        self.run(&[
+            "checkout",
+            "-B",
+            head,
+            &format!("{}/{}", self.remote, branch),
+        ])?;
+
+        let mut args = vec!["cherry-pick", "-x"];
+        if commits.len() == 1 && self.parents(last)? > 1 {
+            args.extend(["-m", "1"]);
+        }
+        args.extend(commits.iter().map(|commit| commit.as_str()));
+
+        if self.git(&args)?.status.success() {
+            return Ok(Picked::Clean);
+        }
+
+        let conflicts = self.run(&["diff", "--name-only", "--diff-filter=U"])?;
+        self.run(&["cherry-pick", "--abort"])?;
+        Ok(Picked::Conflict(
+            conflicts.lines().map(|file| file.to_string()).collect(),
+        ))
+    }
+
+    // push publishes the local branch, replacing what a previous attempt left
+    pub fn push(&self, head: &str) -> Result<()> {
+        self.run(&[
+            "push",
+            "--force",
+            &self.remote,
+            &format!("{head}:refs/heads/{head}"),
+        ])?;
+        Ok(())
+    }
+}
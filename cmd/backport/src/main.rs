@@ -0,0 +1,107 @@
+use std::process::{ExitCode, Termination};
+
+use action::{client, github::GitHub};
+use anyhow::anyhow;
+use backport::Backports;
+use clap::Parser;
+use config::types::{Configuration, Repo};
+use git::Git;
+use reconcile::cli::PlanArgs;
+
+mod backport;
+mod error;
+mod git;
+
+#[derive(clap::Parser, Debug)]
+#[command(
+    author,
+    version = env ! ("CARGO_PKG_VERSION"),
+    long_about = None
+)]
+struct Cli {
+    /// Path to config.yaml, used to find who may run /cherry-pick
+    #[arg(long, default_value = "")]
+    config: String,
+
+    /// Repository in the form org/repo
+    #[arg(long)]
+    repository: String,
+
+    /// Number of the merged PR to backport
+    #[arg(long)]
+    pr: u64,
+
+    /// Path to a clone of the repository, the cherry-picks are made there
+    #[arg(long, default_value = ".")]
+    path: String,
+
+    /// Remote of the clone the backport branches are pushed to
+    #[arg(long, default_value = "origin")]
+    remote: String,
+
+    /// Login of the bot the outcome comments are made with, conflicts it reported are not retried
+    #[arg(long, default_value = "trustificationdemo-ci-bot[bot]")]
+    bot: String,
+
+    #[command(flatten)]
+    plan: PlanArgs,
+}
+
+#[tokio::main]
+async fn main() -> impl Termination {
+    match exec().await {
+        Ok(code) => code,
+        Err(error) => {
+            println!("{:#?}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn exec() -> crate::error::Result<ExitCode> {
+    let cli = Cli::parse();
+
+    let (org, repo) = cli
+        .repository
+        .split_once('/')
+        .ok_or_else(|| anyhow!("repository must be in the form org/repo"))?;
+
+    let configuration = Configuration::from_path(&cli.config)?;
+
+    // Instantiate the client and find the branches the PR goes to
+    let client = client::get_client()?;
+
+    let pull_request = client.get_pull_request(org, repo, cli.pr).await?;
+    let targets =
+        backport::targets(&client, &configuration.commands, org, repo, &pull_request).await?;
+
+    let git = Git {
+        path: cli.path.clone(),
+        remote: cli.remote.clone(),
+    };
+    let backports = Backports {
+        client: &client,
+        git: &git,
+        pull_request: &pull_request,
+        targets,
+        bot: &cli.bot,
+        applied: Default::default(),
+    };
+
+    let repos = vec![Repo {
+        org: org.to_string(),
+        repo: repo.to_string(),
+        ..Default::default()
+    }];
+    let code = reconcile::plan::run(&backports, &repos, &cli.plan).await?;
+
+    // Report the outcome on the original PR
+    if let Some(comment) = backports.comment() {
+        eprintln!("{}", comment);
+        client
+            .create_comment(org, repo, pull_request.number, &comment)
+            .await?;
+    }
+
+    Ok(code)
+}
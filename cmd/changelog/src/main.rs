@@ -0,0 +1,260 @@
+use std::{
+    fs,
+    process::{ExitCode, Termination},
+};
+
+use action::{client, commands::ActionCommand, github::GitHub};
+use anyhow::anyhow;
+use clap::Parser;
+use config::types::{Configuration, TitleConvention};
+use pr::{
+    changelog::{new_contributors, Changelog, PullRequest},
+    convention::Convention,
+};
+
+mod error;
+
+#[derive(clap::Parser, Debug)]
+#[command(
+    author,
+    version = env ! ("CARGO_PKG_VERSION"),
+    long_about = None
+)]
+struct Cli {
+    /// Path to config.yaml, used to find the PR title convention of the repository
+    #[arg(long, default_value = "")]
+    config: String,
+
+    /// Repository in the form org/repo
+    #[arg(long)]
+    repository: String,
+
+    /// Tag of the release (e.g. v1.2.3)
+    #[arg(long)]
+    tag: String,
+
+    /// Previous release tag, defaults to the latest release of the repository, none for a first release
+    #[arg(long, default_value = "")]
+    prev_version: String,
+
+    /// Branch or SHA for the release
+    #[arg(long, default_value = "main")]
+    r#ref: String,
+
+    /// Write the changelog to this file instead of stdout
+    #[arg(long, default_value = "")]
+    output: String,
+}
+
+#[tokio::main]
+async fn main() -> impl Termination {
+    match exec().await {
+        Ok(_) => ExitCode::SUCCESS,
+        Err(error) => {
+            println!("{:#?}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn exec() -> crate::error::Result<()> {
+    let cli = Cli::parse();
+
+    let (org, repo) = cli
+        .repository
+        .split_once('/')
+        .ok_or_else(|| anyhow!("repository must be in the form org/repo"))?;
+
+    let title_convention = if cli.config.is_empty() {
+        TitleConvention::default()
+    } else {
+        let configuration = Configuration::from_path(&cli.config)?;
+        configuration
+            .find_repo(org, repo)
+            .map(|repo| repo.convention.clone())
+            .unwrap_or_default()
+    };
+
+    let client = client::get_client()?;
+    let data = release_notes(
+        &client,
+        title_convention.convention().as_ref(),
+        org,
+        repo,
+        &cli.tag,
+        &cli.prev_version,
+        &cli.r#ref,
+    )
+    .await?;
+
+    if cli.output.is_empty() {
+        println!("{}", data);
+    } else {
+        fs::write(&cli.output, data)?;
+    }
+
+    Ok(())
+}
+
+// release_notes renders the changelog of the pull requests merged since the
+// previous release, or since the beginning of a repository without releases.
+// The new contributors are taken from the notes GitHub generates, they are
+// left out with a warning when GitHub can not generate them.
+async fn release_notes(
+    client: &dyn GitHub,
+    convention: &dyn Convention,
+    org: &str,
+    repo: &str,
+    tag: &str,
+    prev_version: &str,
+    r#ref: &str,
+) -> action::error::Result<String> {
+    let previous = match prev_version {
+        "" => client
+            .get_latest_release(org, repo)
+            .await?
+            .map(|release| release.tag_name),
+        prev_version => Some(prev_version.to_string()),
+    };
+
+    let pull_requests = client
+        .list_merged_pull_requests(org, repo, previous.as_deref().unwrap_or_default(), r#ref)
+        .await?
+        .into_iter()
+        .map(|pull_request| PullRequest {
+            number: pull_request.number,
+            title: pull_request.title,
+            author: pull_request.author,
+            url: pull_request.url,
+        });
+    let changelog = Changelog::new(convention, pull_requests);
+
+    if changelog.is_empty() {
+        ActionCommand::Warning(format!(
+            "No pull requests found between {} and {}",
+            previous.as_deref().unwrap_or("the first commit"),
+            r#ref
+        ))
+        .send_command();
+    }
+
+    let mut notes = changelog.notes(&format!("{org}/{repo}"), previous.as_deref(), tag);
+    match client
+        .generate_release_notes(org, repo, tag, r#ref, previous.as_deref())
+        .await
+    {
+        Ok(generated) => {
+            if let Some(contributors) = new_contributors(&generated) {
+                notes.push_str(contributors);
+                notes.push('\n');
+            }
+        }
+        Err(error) => ActionCommand::Warning(format!(
+            "Generated release notes are unavailable, new contributors are left out: {error}"
+        ))
+        .send_command(),
+    }
+    Ok(notes)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use action::{
+        fake::{FakeGitHub, FakeRepo},
+        github::{PullRequest, Release},
+    };
+    use pr::convention::Emoji;
+
+    use crate::release_notes;
+
+    fn pull_request(number: u64, title: &str, author: &str) -> PullRequest {
+        PullRequest {
+            number,
+            title: title.to_string(),
+            author: Some(author.to_string()),
+            url: Some(format!("https://github.com/org/repo/pull/{}", number)),
+        }
+    }
+
+    #[tokio::test]
+    async fn first_release() {
+        let client = FakeGitHub::new().with_repo(
+            "org",
+            "repo",
+            FakeRepo {
+                compare: HashMap::from([(
+                    "...main".to_string(),
+                    vec![pull_request(1, ":sparkles: Initial import", "octocat")],
+                )]),
+                generated_notes: HashMap::from([(
+                    "v0.1.0".to_string(),
+                    "\
+## What's Changed
+* :sparkles: Initial import by @octocat in https://github.com/org/repo/pull/1
+
+## New Contributors
+* @octocat made their first contribution in https://github.com/org/repo/pull/1
+
+**Full Changelog**: https://github.com/org/repo/commits/v0.1.0"
+                        .to_string(),
+                )]),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(
+            "\
+**Full Changelog**: https://github.com/org/repo/commits/v0.1.0
+
+## :sparkles: Features
+* Initial import by @octocat in https://github.com/org/repo/pull/1
+
+## New Contributors
+* @octocat made their first contribution in https://github.com/org/repo/pull/1
+",
+            release_notes(&client, &Emoji, "org", "repo", "v0.1.0", "", "main")
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn latest_release() {
+        let client = FakeGitHub::new().with_repo(
+            "org",
+            "repo",
+            FakeRepo {
+                releases: vec![Release {
+                    tag_name: "v0.1.0".to_string(),
+                    target_commitish: "main".to_string(),
+                    name: None,
+                    body: None,
+                    draft: false,
+                    prerelease: false,
+                }],
+                compare: HashMap::from([(
+                    "v0.1.0...main".to_string(),
+                    vec![pull_request(2, ":bug: Fix bug", "octocat")],
+                )]),
+                ..Default::default()
+            },
+        );
+
+        // No generated notes, the changelog is still rendered from the pull
+        // requests
+        assert_eq!(
+            "\
+**Full Changelog**: https://github.com/org/repo/compare/v0.1.0...v0.1.1
+
+## :bug: Bug Fixes
+* Fix bug by @octocat in https://github.com/org/repo/pull/2
+
+",
+            release_notes(&client, &Emoji, "org", "repo", "v0.1.1", "", "main")
+                .await
+                .unwrap()
+        );
+    }
+}
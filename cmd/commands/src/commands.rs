@@ -0,0 +1,410 @@
+use action::github::{GitHub, IssuePayload};
+use config::types::{Configuration, Permission};
+use slash::command::{commands, Command};
+use version::branch::Branch;
+
+use crate::error::Result;
+
+// Issue is the issue or pull request a comment is made on.
+pub struct Issue {
+    pub number: u64,
+    pub author: String,
+    pub pull_request: bool,
+}
+
+// Outcome is what a comment led to, problems are replied on the issue.
+#[derive(Debug, Default, PartialEq)]
+pub struct Outcome {
+    pub applied: Vec<String>,
+    pub problems: Vec<String>,
+}
+
+impl Outcome {
+    // reply explains the problems to the commenter in markdown
+    pub fn reply(&self, commenter: &str) -> Option<String> {
+        if self.problems.is_empty() {
+            return None;
+        }
+
+        let mut reply = format!("@{commenter} some commands were not run:\n\n");
+        for problem in &self.problems {
+            reply.push_str(&format!("- {}\n", problem));
+        }
+        Some(reply)
+    }
+}
+
+// Processor runs the slash commands of a comment against its issue or pull
+// request, `Configuration.commands` says who may run them.
+pub struct Processor<'a> {
+    pub client: &'a dyn GitHub,
+    pub configuration: &'a Configuration,
+    pub org: &'a str,
+    pub repo: &'a str,
+    pub issue: Issue,
+}
+
+impl Processor<'_> {
+    pub async fn run(&self, commenter: &str, body: &str) -> Result<Outcome> {
+        let mut outcome = Outcome::default();
+
+        let (commands, problems) = commands(body, &self.configuration.commands.labels);
+        outcome
+            .problems
+            .extend(problems.iter().map(|problem| problem.to_string()));
+        if commands.is_empty() {
+            return Ok(outcome);
+        }
+
+        let role = self
+            .client
+            .get_permission(self.org, self.repo, commenter)
+            .await?;
+        let labels = self
+            .client
+            .list_issue_labels(self.org, self.repo, self.issue.number)
+            .await?;
+
+        for command in commands {
+            let permission = self.configuration.commands.permission(&command.name());
+            let author = self.issue.author.eq_ignore_ascii_case(commenter);
+            if !permission.allows(&role, author) {
+                outcome.problems.push(format!(
+                    "`{}`: only {} can run it",
+                    command,
+                    describe(permission)
+                ));
+                continue;
+            }
+
+            match self.execute(commenter, &labels, &command).await? {
+                Ok(Some(applied)) => outcome.applied.push(applied),
+                Ok(None) => {}
+                Err(problem) => outcome.problems.push(format!("`{}`: {}", command, problem)),
+            }
+        }
+
+        Ok(outcome)
+    }
+
+    // execute runs a command, it returns what was done, nothing when there
+    // was nothing to do, or the reason it was not run
+    async fn execute(
+        &self,
+        commenter: &str,
+        labels: &[String],
+        command: &Command,
+    ) -> Result<std::result::Result<Option<String>, String>> {
+        let (org, repo, number) = (self.org, self.repo, self.issue.number);
+        let has_label = |name: &str| labels.iter().any(|l| l.eq_ignore_ascii_case(name));
+
+        match command {
+            Command::AddLabel(prefix, name) => {
+                let wanted = format!("{prefix}/{name}");
+                let Some(label) = self
+                    .labels()
+                    .into_iter()
+                    .find(|label| label.eq_ignore_ascii_case(&wanted))
+                else {
+                    return Ok(Err(format!(
+                        "the label {:?} is not managed in this repository",
+                        wanted
+                    )));
+                };
+                if has_label(&label) {
+                    return Ok(Ok(None));
+                }
+
+                self.client
+                    .add_issue_labels(org, repo, number, std::slice::from_ref(&label))
+                    .await?;
+                Ok(Ok(Some(format!("added label {:?}", label))))
+            }
+            Command::RemoveLabel(prefix, name) => {
+                let unwanted = format!("{prefix}/{name}");
+                let Some(label) = labels.iter().find(|l| l.eq_ignore_ascii_case(&unwanted)) else {
+                    return Ok(Ok(None));
+                };
+
+                self.client
+                    .remove_issue_label(org, repo, number, label)
+                    .await?;
+                Ok(Ok(Some(format!("removed label {:?}", label))))
+            }
+            Command::Milestone(title) => {
+                let milestones = self.client.list_milestones(org, repo).await?;
+                let Some(milestone) = milestones
+                    .iter()
+                    .find(|m| m.title.eq_ignore_ascii_case(title))
+                else {
+                    return Ok(Err(format!(
+                        "there is no open milestone {:?} in this repository",
+                        title
+                    )));
+                };
+
+                self.client
+                    .set_issue_milestone(org, repo, number, Some(milestone.number))
+                    .await?;
+                Ok(Ok(Some(format!("set milestone {:?}", milestone.title))))
+            }
+            Command::RemoveMilestone => {
+                self.client
+                    .set_issue_milestone(org, repo, number, None)
+                    .await?;
+                Ok(Ok(Some("removed milestone".to_string())))
+            }
+            Command::Assign(logins) | Command::Unassign(logins) => {
+                let logins = match logins.is_empty() {
+                    true => vec![commenter.to_string()],
+                    false => logins.clone(),
+                };
+
+                if let Command::Assign(_) = command {
+                    self.client
+                        .add_assignees(org, repo, number, &logins)
+                        .await?;
+                    Ok(Ok(Some(format!("assigned {}", logins.join(" ")))))
+                } else {
+                    self.client
+                        .remove_assignees(org, repo, number, &logins)
+                        .await?;
+                    Ok(Ok(Some(format!("unassigned {}", logins.join(" ")))))
+                }
+            }
+            Command::CherryPick(branch) => {
+                if !self.issue.pull_request {
+                    return Ok(Err("only pull requests can be cherry-picked".to_string()));
+                }
+                if !matches!(branch.parse::<Branch>(), Ok(Branch::Release { .. })) {
+                    return Ok(Err(format!(
+                        "{:?} is not a release branch, expected release-X.Y",
+                        branch
+                    )));
+                }
+                if self
+                    .client
+                    .get_ref(org, repo, &format!("heads/{branch}"))
+                    .await?
+                    .is_none()
+                {
+                    return Ok(Err(format!("the branch {:?} does not exist", branch)));
+                }
+
+                let label = format!("cherry-pick/{branch}");
+                if has_label(&label) {
+                    return Ok(Ok(None));
+                }
+                self.client
+                    .add_issue_labels(org, repo, number, std::slice::from_ref(&label))
+                    .await?;
+                Ok(Ok(Some(format!("added label {:?}", label))))
+            }
+            Command::Close | Command::Reopen => {
+                let state = match command {
+                    Command::Close => "closed",
+                    _ => "open",
+                };
+                let payload = IssuePayload {
+                    state: Some(state.to_string()),
+                    ..Default::default()
+                };
+                self.client
+                    .update_issue(org, repo, number, &payload)
+                    .await?;
+                Ok(Ok(Some(format!("set state {:?}", state))))
+            }
+            Command::Retitle(title) => {
+                let payload = IssuePayload {
+                    title: Some(title.clone()),
+                    ..Default::default()
+                };
+                self.client
+                    .update_issue(org, repo, number, &payload)
+                    .await?;
+                Ok(Ok(Some(format!("set title {:?}", title))))
+            }
+        }
+    }
+
+    // labels returns the names of the labels config.yaml manages in the
+    // repository, or the shared ones when it is not listed
+    fn labels(&self) -> Vec<String> {
+        let labels = match self.configuration.find_repo(self.org, self.repo) {
+            Some(repo) => self.configuration.labels_for(repo),
+            None => self.configuration.labels.clone(),
+        };
+        labels.into_iter().map(|label| label.name).collect()
+    }
+}
+
+fn describe(permission: Permission) -> &'static str {
+    match permission {
+        Permission::Anyone => "anyone",
+        Permission::Author => "the author or a collaborator",
+        Permission::Collaborator => "collaborators",
+        Permission::Maintainer => "maintainers",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use action::{
+        fake::{FakeGitHub, FakeRepo},
+        github::{Issue, Milestone, Reference},
+    };
+    use config::types::Configuration;
+
+    use crate::commands::{self, Outcome, Processor};
+
+    const CONFIG: &str = r#"
+repos:
+  - org: org
+    repo: repo
+labels:
+  - name: kind/bug
+    color: e11d21
+  - name: kind/feature
+    color: c7def8
+  - name: priority/backlog
+    color: fef2c0
+milestones: []
+commands:
+  labels: [kind, priority]
+  permissions:
+    kind: author
+    milestone: maintainer
+"#;
+
+    fn client() -> FakeGitHub {
+        FakeGitHub::new().with_repo(
+            "org",
+            "repo",
+            FakeRepo {
+                milestones: vec![Milestone {
+                    number: 3,
+                    title: "Trustify 2.1.0".to_string(),
+                    description: None,
+                    state: Some("open".to_string()),
+                    due_on: None,
+                    open_issues: Some(0),
+                }],
+                issues: vec![Issue {
+                    number: 1,
+                    title: "Fix the bug".to_string(),
+                    state: "open".to_string(),
+                    milestone: None,
+                    pull_request: true,
+                }],
+                refs: vec![Reference {
+                    name: "refs/heads/release-0.2".to_string(),
+                    sha: "abc".to_string(),
+                }],
+                issue_labels: HashMap::from([(1, vec!["priority/backlog".to_string()])]),
+                permissions: HashMap::from([
+                    ("maintainer".to_string(), "maintain".to_string()),
+                    ("developer".to_string(), "write".to_string()),
+                ]),
+                ..Default::default()
+            },
+        )
+    }
+
+    fn processor<'a>(client: &'a FakeGitHub, configuration: &'a Configuration) -> Processor<'a> {
+        Processor {
+            client,
+            configuration,
+            org: "org",
+            repo: "repo",
+            issue: commands::Issue {
+                number: 1,
+                author: "author".to_string(),
+                pull_request: true,
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn collaborator() {
+        let configuration: Configuration = serde_yml::from_str(CONFIG).unwrap();
+        let client = client();
+
+        let outcome = processor(&client, &configuration)
+            .run(
+                "developer",
+                "\
+/kind bug feature documentation
+/remove-priority backlog
+/milestone Trustify 2.1.0
+/assign
+/cherry-pick release-0.2 release-0.3 main
+/retitle :bug: Fix the bug
+```
+/close
+```
+",
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            Outcome {
+                applied: vec![
+                    "added label \"kind/bug\"".to_string(),
+                    "added label \"kind/feature\"".to_string(),
+                    "removed label \"priority/backlog\"".to_string(),
+                    "assigned developer".to_string(),
+                    "added label \"cherry-pick/release-0.2\"".to_string(),
+                    "set title \":bug: Fix the bug\"".to_string(),
+                ],
+                problems: vec![
+                    "`/kind documentation`: the label \"kind/documentation\" is not managed in this repository".to_string(),
+                    "`/milestone Trustify 2.1.0`: only maintainers can run it".to_string(),
+                    "`/cherry-pick release-0.3`: the branch \"release-0.3\" does not exist".to_string(),
+                    "`/cherry-pick main`: \"main\" is not a release branch, expected release-X.Y".to_string(),
+                ],
+            },
+            outcome
+        );
+
+        let repo = client.repo("org", "repo");
+        assert_eq!(
+            vec!["kind/bug", "kind/feature", "cherry-pick/release-0.2"],
+            repo.issue_labels[&1]
+        );
+        assert_eq!(vec!["developer"], repo.assignees[&1]);
+        assert_eq!(":bug: Fix the bug", repo.issues[0].title);
+        assert_eq!("open", repo.issues[0].state);
+    }
+
+    #[tokio::test]
+    async fn permissions() {
+        let configuration: Configuration = serde_yml::from_str(CONFIG).unwrap();
+        let client = client();
+
+        let outcome = processor(&client, &configuration)
+            .run("author", "/kind bug\n/close\n")
+            .await
+            .unwrap();
+        assert_eq!(vec!["added label \"kind/bug\""], outcome.applied);
+        assert_eq!(
+            Some(
+                "@author some commands were not run:\n\n- `/close`: only collaborators can run it\n"
+                    .to_string()
+            ),
+            outcome.reply("author")
+        );
+
+        let outcome = processor(&client, &configuration)
+            .run("maintainer", "/milestone trustify 2.1.0\n/close\n")
+            .await
+            .unwrap();
+        assert!(outcome.problems.is_empty());
+
+        let repo = client.repo("org", "repo");
+        assert_eq!(Some(3), repo.issues[0].milestone);
+        assert_eq!("closed", repo.issues[0].state);
+    }
+}
@@ -0,0 +1,22 @@
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("GITHUB_REPOSITORY is not org/repo: {0:?}")]
+    InvalidRepository(String),
+
+    #[error("Unable to parse the event {file_path:?}: {err}")]
+    UnmarshalEvent {
+        file_path: String,
+        err: serde_json::Error,
+    },
+
+    #[error(transparent)]
+    Config(#[from] config::error::Error),
+
+    #[error(transparent)]
+    Action(#[from] action::error::Error),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
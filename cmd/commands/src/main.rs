@@ -0,0 +1,112 @@
+use std::{
+    fs,
+    process::{ExitCode, Termination},
+};
+
+use action::{client, context::GitHubVariables, github::GitHub};
+use clap::Parser;
+use commands::{Issue, Processor};
+use serde::Deserialize;
+
+mod commands;
+mod error;
+
+#[derive(clap::Parser, Debug)]
+#[command(
+    author,
+    version = env ! ("CARGO_PKG_VERSION"),
+    long_about = None
+)]
+struct Cli {
+    /// Path to config.yaml
+    #[arg(long, default_value = "")]
+    config: String,
+}
+
+// Event is the part of the `issue_comment` event we need
+#[derive(Debug, Deserialize)]
+struct Event {
+    comment: EventComment,
+    issue: EventIssue,
+}
+
+#[derive(Debug, Deserialize)]
+struct EventComment {
+    body: String,
+    user: User,
+}
+
+#[derive(Debug, Deserialize)]
+struct EventIssue {
+    number: u64,
+    user: User,
+    // Only set on pull requests
+    pull_request: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct User {
+    login: String,
+}
+
+#[tokio::main]
+async fn main() -> impl Termination {
+    match exec().await {
+        Ok(code) => code,
+        Err(error) => {
+            println!("{:#?}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn exec() -> crate::error::Result<ExitCode> {
+    let cli = Cli::parse();
+
+    let configuration = config::types::Configuration::from_path(&cli.config)?;
+
+    // Find the comment and the issue or PR it is made on
+    let gh_context = GitHubVariables::from_env()?;
+    let (org, repo) = gh_context.repository().ok_or_else(|| {
+        crate::error::Error::InvalidRepository(gh_context.github_repository.clone())
+    })?;
+
+    let event_file = fs::read_to_string(&gh_context.github_event_path)?;
+    let event: Event =
+        serde_json::from_str(&event_file).map_err(|err| crate::error::Error::UnmarshalEvent {
+            file_path: gh_context.github_event_path.clone(),
+            err,
+        })?;
+
+    // Instantiate the client and run the commands of the comment
+    let client = client::get_client()?;
+
+    let processor = Processor {
+        client: &client,
+        configuration: &configuration,
+        org,
+        repo,
+        issue: Issue {
+            number: event.issue.number,
+            author: event.issue.user.login,
+            pull_request: event.issue.pull_request.is_some(),
+        },
+    };
+
+    let commenter = event.comment.user.login;
+    let outcome = processor.run(&commenter, &event.comment.body).await?;
+    for applied in &outcome.applied {
+        println!("{}", applied);
+    }
+
+    match outcome.reply(&commenter) {
+        Some(reply) => {
+            println!("{}", reply);
+            client
+                .create_comment(org, repo, event.issue.number, &reply)
+                .await?;
+            Ok(ExitCode::FAILURE)
+        }
+        None => Ok(ExitCode::SUCCESS),
+    }
+}
@@ -0,0 +1,138 @@
+use std::process::{ExitCode, Termination};
+
+use action::{client, github::Release};
+use anyhow::anyhow;
+use clap::Parser;
+use config::types::{Configuration, Repo, TitleConvention};
+use reconcile::cli::PlanArgs;
+use release::Releases;
+use version::{branch::Branch, version::Version};
+
+mod error;
+mod release;
+
+#[derive(clap::Parser, Debug)]
+#[command(
+    author,
+    version = env ! ("CARGO_PKG_VERSION"),
+    long_about = None
+)]
+struct Cli {
+    /// Path to config.yaml, used to find the PR title convention of the repository
+    #[arg(long, default_value = "")]
+    config: String,
+
+    /// Repository in the form org/repo
+    #[arg(long)]
+    repository: String,
+
+    /// Tag of the release (e.g. v1.2.3 or v1.3.0-alpha.1)
+    #[arg(long)]
+    tag: String,
+
+    /// Branch the release is made from, main or release-X.Y
+    #[arg(long, default_value = "main")]
+    branch: String,
+
+    /// Branch or SHA the tag is created from, defaults to the branch
+    #[arg(long, default_value = "")]
+    r#ref: String,
+
+    /// Previous release tag, defaults to the release before this one
+    #[arg(long, default_value = "")]
+    prev_version: String,
+
+    /// Create the release as a draft
+    #[arg(long, default_value = "false")]
+    draft: bool,
+
+    #[command(flatten)]
+    plan: PlanArgs,
+}
+
+#[tokio::main]
+async fn main() -> impl Termination {
+    match exec().await {
+        Ok(code) => code,
+        Err(error) => {
+            println!("{:#?}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn exec() -> crate::error::Result<ExitCode> {
+    let cli = Cli::parse();
+
+    let (org, repo) = cli
+        .repository
+        .split_once('/')
+        .ok_or_else(|| anyhow!("repository must be in the form org/repo"))?;
+
+    // Validate the tag with the versioning rules before anything else
+    let version: Version = cli.tag.parse()?;
+    let branch: Branch = cli.branch.parse()?;
+    branch.check(&version)?;
+
+    let title_convention = if cli.config.is_empty() {
+        TitleConvention::default()
+    } else {
+        let configuration = Configuration::from_path(&cli.config)?;
+        configuration
+            .find_repo(org, repo)
+            .map(|repo| repo.convention.clone())
+            .unwrap_or_default()
+    };
+
+    let client = client::get_client()?;
+
+    let target = match cli.r#ref.as_str() {
+        "" => branch.to_string(),
+        r#ref => r#ref.to_string(),
+    };
+
+    let mut release = Release {
+        tag_name: version.to_string(),
+        target_commitish: target,
+        name: Some(version.to_string()),
+        body: None,
+        draft: cli.draft,
+        prerelease: version.is_prerelease(),
+    };
+
+    let previous = match cli.prev_version.as_str() {
+        "" => release::previous_tag(&client, org, repo, &version)
+            .await?
+            .map(|v| v.to_string()),
+        prev_version => Some(prev_version.to_string()),
+    };
+
+    release.body = match previous {
+        Some(previous) => Some(
+            release::notes(
+                &client,
+                title_convention.convention().as_ref(),
+                org,
+                repo,
+                &previous,
+                &release,
+            )
+            .await?,
+        ),
+        None => Some(format!("First release of {}", cli.repository)),
+    };
+
+    let releases = Releases {
+        client: &client,
+        release,
+    };
+    let repos = vec![Repo {
+        org: org.to_string(),
+        repo: repo.to_string(),
+        ..Default::default()
+    }];
+
+    let code = reconcile::plan::run(&releases, &repos, &cli.plan).await?;
+
+    Ok(code)
+}
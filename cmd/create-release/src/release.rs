@@ -0,0 +1,273 @@
+use std::fmt;
+
+use action::{
+    commands::ActionCommand,
+    github::{GitHub, Release},
+};
+use async_trait::async_trait;
+use config::types::Repo;
+use pr::{
+    changelog::{Changelog, PullRequest},
+    convention::Convention,
+};
+use reconcile::{error::Result, resource::Resource};
+use serde::{Deserialize, Serialize};
+use version::{next::previous_of, version::Version};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum Why {
+    // Wanted
+    Missing(Release),
+}
+
+impl fmt::Display for Why {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Why::Missing(wanted) => {
+                let kind = match (wanted.draft, wanted.prerelease) {
+                    (true, true) => "draft pre-release",
+                    (true, false) => "draft release",
+                    (false, true) => "pre-release",
+                    (false, false) => "release",
+                };
+                write!(
+                    f,
+                    "create {} {:?} from {}\n\n{}",
+                    kind,
+                    wanted.tag_name,
+                    wanted.target_commitish,
+                    wanted.body.as_deref().unwrap_or_default()
+                )
+            }
+        }
+    }
+}
+
+// previous_tag returns the tag the release notes start from, releases that
+// don't follow VERSIONING.md are ignored.
+pub async fn previous_tag(
+    client: &dyn GitHub,
+    org: &str,
+    repo: &str,
+    version: &Version,
+) -> action::error::Result<Option<Version>> {
+    let tags: Vec<Version> = client
+        .list_tags(org, repo)
+        .await?
+        .iter()
+        .filter_map(|r| r.name.strip_prefix("refs/tags/"))
+        .filter_map(|name| name.parse().ok())
+        .collect();
+
+    Ok(previous_of(version, &tags).cloned())
+}
+
+// notes renders the release notes from the pull requests merged between the
+// previous tag and the target of the release.
+pub async fn notes(
+    client: &dyn GitHub,
+    convention: &dyn Convention,
+    org: &str,
+    repo: &str,
+    previous: &str,
+    release: &Release,
+) -> action::error::Result<String> {
+    let pull_requests = client
+        .list_merged_pull_requests(org, repo, previous, &release.target_commitish)
+        .await?
+        .into_iter()
+        .map(|pull_request| PullRequest {
+            number: pull_request.number,
+            title: pull_request.title,
+            author: pull_request.author,
+            url: pull_request.url,
+        });
+    let changelog = Changelog::new(convention, pull_requests);
+
+    if changelog.is_empty() {
+        ActionCommand::Warning(format!(
+            "No pull requests found between {} and {}",
+            previous, release.target_commitish
+        ))
+        .send_command();
+    }
+
+    Ok(changelog.notes(&format!("{org}/{repo}"), Some(previous), &release.tag_name))
+}
+
+// Releases creates the GitHub release of a tag, a release that already exists
+// is left alone.
+pub struct Releases<'a> {
+    pub client: &'a dyn GitHub,
+    pub release: Release,
+}
+
+#[async_trait]
+impl Resource for Releases<'_> {
+    const KIND: &'static str = "Release";
+
+    type Desired = Release;
+    type Observed = Option<Release>;
+    type Change = Why;
+
+    fn desired(&self, _repo: &Repo) -> Vec<Release> {
+        vec![self.release.clone()]
+    }
+
+    async fn observe(&self, org: &str, repo: &str) -> Result<Option<Release>> {
+        Ok(self
+            .client
+            .get_release(org, repo, &self.release.tag_name)
+            .await?)
+    }
+
+    fn diff(&self, desired: &[Release], observed: &Option<Release>) -> Vec<Why> {
+        if observed.is_some() {
+            return vec![];
+        }
+
+        desired
+            .iter()
+            .map(|wanted| Why::Missing(wanted.clone()))
+            .collect()
+    }
+
+    fn warnings(&self, _desired: &[Release], observed: &Option<Release>) -> Vec<String> {
+        match observed {
+            Some(current) => vec![format!("release {:?} already exists", current.tag_name)],
+            None => vec![],
+        }
+    }
+
+    async fn apply(&self, org: &str, repo: &str, change: &Why) -> Result<()> {
+        match change {
+            Why::Missing(wanted) => {
+                let resp = self.client.create_release(org, repo, wanted).await?;
+                eprintln!("Release created: {:?}", resp.tag_name);
+            }
+        };
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use action::{
+        fake::{FakeGitHub, FakeRepo},
+        github::{PullRequest, Reference, Release},
+    };
+    use config::types::Repo;
+    use pr::convention::Emoji;
+    use reconcile::{
+        cli::{Output, PlanArgs},
+        plan::{run, Plan},
+    };
+
+    use crate::release::{notes, previous_tag, Releases};
+
+    fn tag(name: &str) -> Reference {
+        Reference {
+            name: format!("refs/tags/{name}"),
+            sha: name.to_string(),
+        }
+    }
+
+    fn pull_request(number: u64, title: &str) -> PullRequest {
+        PullRequest {
+            number,
+            title: title.to_string(),
+            author: None,
+            url: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn create() {
+        let client = FakeGitHub::new().with_repo(
+            "org",
+            "repo",
+            FakeRepo {
+                refs: vec![tag("v0.2.0"), tag("v0.3.0-alpha.1"), tag("v0.3.0-alpha.2")],
+                compare: HashMap::from([(
+                    "v0.2.0...release-0.3".to_string(),
+                    vec![
+                        pull_request(1, ":sparkles: Add a feature"),
+                        pull_request(2, ":bug: Fix a bug"),
+                    ],
+                )]),
+                ..Default::default()
+            },
+        );
+        let repos = vec![Repo {
+            org: "org".to_string(),
+            repo: "repo".to_string(),
+            ..Default::default()
+        }];
+
+        let version = "v0.3.0".parse().unwrap();
+        let previous = previous_tag(&client, "org", "repo", &version)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!("v0.2.0", previous.to_string());
+
+        let mut release = Release {
+            tag_name: "v0.3.0".to_string(),
+            target_commitish: "release-0.3".to_string(),
+            name: Some("v0.3.0".to_string()),
+            body: None,
+            draft: true,
+            prerelease: false,
+        };
+        release.body = Some(
+            notes(
+                &client,
+                &Emoji,
+                "org",
+                "repo",
+                &previous.to_string(),
+                &release,
+            )
+            .await
+            .unwrap(),
+        );
+
+        let releases = Releases {
+            client: &client,
+            release,
+        };
+
+        let plan = Plan::compute(&releases, &repos).await.unwrap();
+        assert_eq!(
+            "\
+org/repo: create draft release \"v0.3.0\" from release-0.3
+
+**Full Changelog**: https://github.com/org/repo/compare/v0.2.0...v0.3.0
+
+## :sparkles: Features
+* Add a feature (#1)
+
+## :bug: Bug Fixes
+* Fix a bug (#2)
+
+",
+            plan.render(Output::Text).unwrap()
+        );
+
+        let args = PlanArgs {
+            confirm: true,
+            output: Output::Text,
+            ..Default::default()
+        };
+        run(&releases, &repos, &args).await.unwrap();
+
+        assert_eq!(1, client.repo("org", "repo").releases.len());
+
+        let plan = Plan::compute(&releases, &repos).await.unwrap();
+        assert!(plan.is_empty());
+        assert_eq!(1, plan.warnings.len());
+    }
+}
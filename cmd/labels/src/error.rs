@@ -8,6 +8,9 @@ pub enum Error {
     #[error(transparent)]
     Action(#[from] action::error::Error),
 
+    #[error(transparent)]
+    Reconcile(#[from] reconcile::error::Error),
+
     #[error(transparent)]
     Octocrab(#[from] octocrab::Error),
 
@@ -0,0 +1,513 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use action::github::GitHub;
+use async_trait::async_trait;
+use config::types::{Configuration, Label, Repo};
+use reconcile::{error::Result, resource::Resource};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum Why {
+    // Wanted
+    Missing(Label),
+    // Wanted, Current
+    Changed(Label, Label),
+    // Wanted, Current
+    Renamed(Label, Label),
+    // Current
+    Unmanaged(Label),
+    // Current, Branch
+    Retired(Label, String),
+}
+
+impl fmt::Display for Why {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Why::Missing(wanted) => write!(
+                f,
+                "create label {:?} (color: {}, description: {:?})",
+                wanted.name, wanted.color, wanted.description
+            ),
+            Why::Changed(wanted, current) => write!(
+                f,
+                "update label {:?} (color: {} -> {}, description: {:?} -> {:?})",
+                wanted.name, current.color, wanted.color, current.description, wanted.description
+            ),
+            Why::Renamed(wanted, current) => {
+                write!(f, "rename label {:?} to {:?}", current.name, wanted.name)
+            }
+            Why::Unmanaged(current) => write!(f, "delete label {:?}", current.name),
+            Why::Retired(current, branch) => write!(
+                f,
+                "delete label {:?} (branch {:?} is gone)",
+                current.name, branch
+            ),
+        }
+    }
+}
+
+// Labels reconciles the labels of a repository with `Configuration.labels`
+// and the labels `Configuration.label_templates` generate from its branches.
+pub struct Labels<'a> {
+    pub client: &'a dyn GitHub,
+    pub configuration: &'a Configuration,
+
+    // Prune reports labels that are not in the configuration
+    pub prune: bool,
+}
+
+// Observed holds the labels of a repository and its branches, for the labels
+// generated by `Configuration.label_templates`.
+#[derive(Serialize, Deserialize, PartialEq)]
+pub struct Observed {
+    labels: Vec<action::github::Label>,
+    branches: Vec<String>,
+}
+
+impl Labels<'_> {
+    // generated expands the label templates for the branches of the repository
+    fn generated(&self, branches: &[String]) -> Vec<Label> {
+        self.configuration
+            .label_templates
+            .iter()
+            .flat_map(|template| {
+                branches
+                    .iter()
+                    .filter(|branch| template.matches(branch))
+                    .map(|branch| template.expand(branch))
+            })
+            .collect()
+    }
+
+    // retired returns the branch of a generated label whose branch is gone
+    fn retired(&self, name: &str, branches: &[String]) -> Option<String> {
+        self.configuration
+            .label_templates
+            .iter()
+            .filter_map(|template| template.branch_of(name))
+            .find(|branch| !branches.contains(branch))
+    }
+}
+
+fn to_label(label: &action::github::Label) -> Label {
+    Label {
+        name: label.name.clone(),
+        color: label.color.clone(),
+        description: label.description.clone(),
+        previously: vec![],
+    }
+}
+
+fn from_label(label: &Label) -> action::github::Label {
+    action::github::Label {
+        name: label.name.clone(),
+        color: label.color.clone(),
+        description: label.description.clone(),
+    }
+}
+
+#[async_trait]
+impl Resource for Labels<'_> {
+    const KIND: &'static str = "Labels";
+
+    type Desired = Label;
+    type Observed = Observed;
+    type Change = Why;
+
+    fn desired(&self, repo: &Repo) -> Vec<Label> {
+        self.configuration.labels_for(repo)
+    }
+
+    async fn observe(&self, org: &str, repo: &str) -> Result<Observed> {
+        let labels = self.client.list_labels(org, repo).await?;
+
+        // Branches are only needed to expand the templates
+        let mut branches: Vec<String> = vec![];
+        if !self.configuration.label_templates.is_empty() {
+            branches = self
+                .client
+                .list_branches(org, repo)
+                .await?
+                .into_iter()
+                .filter_map(|r| r.name.strip_prefix("refs/heads/").map(|b| b.to_string()))
+                .collect();
+        }
+
+        Ok(Observed { labels, branches })
+    }
+
+    fn diff(&self, desired: &[Label], observed: &Observed) -> Vec<Why> {
+        let mut updates: Vec<Why> = vec![];
+
+        // Labels of config.yaml win over generated ones with the same name
+        let mut desired = desired.to_vec();
+        for label in self.generated(&observed.branches) {
+            if !desired
+                .iter()
+                .any(|l| l.name.eq_ignore_ascii_case(&label.name))
+            {
+                desired.push(label);
+            }
+        }
+        let desired = desired.as_slice();
+        let observed_labels = &observed.labels;
+
+        let current_labes_map: HashMap<String, &action::github::Label> = observed_labels
+            .iter()
+            .map(|l| (l.name.clone(), l))
+            .collect();
+
+        // Compare labels
+        for label in desired {
+            match current_labes_map.get(&label.name) {
+                None => {
+                    let previous_label = label
+                        .previously
+                        .iter()
+                        .find_map(|name| current_labes_map.get(name));
+
+                    let why = match previous_label {
+                        Some(existing_label) => {
+                            Why::Renamed(label.clone(), to_label(existing_label))
+                        }
+                        None => Why::Missing(label.clone()),
+                    };
+
+                    updates.push(why);
+                }
+                Some(existing_label) => {
+                    let empty_string = "".to_string();
+                    if existing_label.color.to_lowercase() != label.color.to_lowercase()
+                        || existing_label.description.as_ref().unwrap_or(&empty_string)
+                            != label.description.as_deref().unwrap_or(&empty_string)
+                    {
+                        updates.push(Why::Changed(label.clone(), to_label(existing_label)));
+                    };
+                }
+            };
+        }
+
+        // Generated labels of deleted branches
+        let mut retired_labels: HashSet<String> = HashSet::new();
+        for existing_label in observed_labels {
+            let wanted = desired
+                .iter()
+                .any(|l| l.name.eq_ignore_ascii_case(&existing_label.name));
+            if wanted {
+                continue;
+            }
+            if let Some(branch) = self.retired(&existing_label.name, &observed.branches) {
+                retired_labels.insert(existing_label.name.to_lowercase());
+                updates.push(Why::Retired(to_label(existing_label), branch));
+            }
+        }
+
+        if self.prune {
+            // Former names are never pruned, they still hold the issue
+            // associations of a label that is about to be renamed.
+            let wanted_labels: HashSet<String> = desired
+                .iter()
+                .flat_map(|l| std::iter::once(&l.name).chain(l.previously.iter()))
+                .map(|name| name.to_lowercase())
+                .collect();
+
+            let mut unmanaged_labels: Vec<&action::github::Label> = observed_labels
+                .iter()
+                .filter(|l| !wanted_labels.contains(&l.name.to_lowercase()))
+                .filter(|l| !retired_labels.contains(&l.name.to_lowercase()))
+                .filter(|l| !self.configuration.prune.is_ignored(&l.name))
+                .collect();
+            unmanaged_labels.sort_by(|a, b| a.name.cmp(&b.name));
+
+            for existing_label in unmanaged_labels {
+                updates.push(Why::Unmanaged(to_label(existing_label)));
+            }
+        }
+
+        updates
+    }
+
+    async fn apply(&self, org: &str, repo: &str, change: &Why) -> Result<()> {
+        match change {
+            Why::Missing(wanted) => {
+                let resp = self
+                    .client
+                    .create_label(org, repo, &from_label(wanted))
+                    .await?;
+                eprintln!("Label created: {:?}", resp);
+            }
+            Why::Changed(wanted, current) | Why::Renamed(wanted, current) => {
+                let resp = self
+                    .client
+                    .update_label(org, repo, &current.name, &from_label(wanted))
+                    .await?;
+                eprintln!("Label updated: {:?}", resp);
+            }
+            Why::Unmanaged(current) | Why::Retired(current, _) => {
+                self.client.delete_label(org, repo, &current.name).await?;
+                eprintln!(
+                    "Label deleted: org: {:?} repo: {:?} label: {:?}",
+                    org, repo, current.name
+                );
+            }
+        };
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use action::{
+        fake::{FakeGitHub, FakeRepo},
+        github::{Label, Reference},
+    };
+    use config::types::Configuration;
+    use reconcile::{
+        cli::{Output, PlanArgs},
+        plan::{run, Plan},
+    };
+
+    use crate::labels::Labels;
+
+    const CONFIG: &str = r#"
+repos:
+  - org: org
+    repo: repo
+labels:
+  - name: kind/bug
+    color: e11d21
+    description: Categorizes issue or PR as related to a bug.
+  - name: kind/feature
+    color: c7def8
+    previously:
+      - enhancement
+  - name: lgtm
+    color: 15dd18
+milestones: []
+prune:
+  ignore:
+    - "cherry-pick/*"
+"#;
+
+    fn label(name: &str, color: &str, description: Option<&str>) -> Label {
+        Label {
+            name: name.to_string(),
+            color: color.to_string(),
+            description: description.map(|d| d.to_string()),
+        }
+    }
+
+    fn client() -> FakeGitHub {
+        FakeGitHub::new().with_repo(
+            "org",
+            "repo",
+            FakeRepo {
+                labels: vec![
+                    label("kind/bug", "E11D21", None),
+                    label("enhancement", "a2eeef", Some("New feature or request")),
+                    label("good first issue", "7057ff", None),
+                    label("cherry-pick/release-0.2", "fef2a0", None),
+                ],
+                ..Default::default()
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn diff() {
+        let configuration: Configuration = serde_yml::from_str(CONFIG).unwrap();
+        let client = client();
+
+        let labels = Labels {
+            client: &client,
+            configuration: &configuration,
+            prune: true,
+        };
+
+        let plan = Plan::compute(&labels, &configuration.repos).await.unwrap();
+        assert_eq!(
+            "\
+org/repo: update label \"kind/bug\" (color: E11D21 -> e11d21, description: None -> Some(\"Categorizes issue or PR as related to a bug.\"))
+org/repo: rename label \"enhancement\" to \"kind/feature\"
+org/repo: create label \"lgtm\" (color: 15dd18, description: None)
+org/repo: delete label \"good first issue\"",
+            plan.render(Output::Text).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn apply() {
+        let configuration: Configuration = serde_yml::from_str(CONFIG).unwrap();
+        let client = client();
+
+        let labels = Labels {
+            client: &client,
+            configuration: &configuration,
+            prune: true,
+        };
+
+        let args = PlanArgs {
+            confirm: true,
+            output: Output::Text,
+            ..Default::default()
+        };
+        run(&labels, &configuration.repos, &args).await.unwrap();
+
+        assert_eq!(
+            vec![
+                label(
+                    "kind/bug",
+                    "e11d21",
+                    Some("Categorizes issue or PR as related to a bug.")
+                ),
+                label("kind/feature", "c7def8", None),
+                label("cherry-pick/release-0.2", "fef2a0", None),
+                label("lgtm", "15dd18", None),
+            ],
+            client.repo("org", "repo").labels
+        );
+        assert!(Plan::compute(&labels, &configuration.repos)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    // json_output runs a confirmed apply with `--output json` in a child
+    // process, stdout can only be read from outside, and parses what was
+    // printed between the markers as the plan document.
+    #[test]
+    fn json_output() {
+        const CHILD: &str = "LABELS_JSON_OUTPUT_CHILD";
+        const BEGIN: &str = "<<<json_output";
+        const END: &str = "json_output>>>";
+
+        if std::env::var(CHILD).is_ok() {
+            let configuration: Configuration = serde_yml::from_str(CONFIG).unwrap();
+            let client = client();
+            let labels = Labels {
+                client: &client,
+                configuration: &configuration,
+                prune: true,
+            };
+            let args = PlanArgs {
+                confirm: true,
+                output: Output::Json,
+                ..Default::default()
+            };
+
+            println!("{BEGIN}");
+            tokio::runtime::Runtime::new()
+                .unwrap()
+                .block_on(run(&labels, &configuration.repos, &args))
+                .unwrap();
+            println!("{END}");
+            return;
+        }
+
+        let output = std::process::Command::new(std::env::current_exe().unwrap())
+            .args(["--exact", "labels::tests::json_output", "--nocapture"])
+            .env(CHILD, "1")
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let start = stdout.find(BEGIN).unwrap() + BEGIN.len();
+        let end = stdout.find(END).unwrap();
+        let document: serde_json::Value = serde_json::from_str(&stdout[start..end]).unwrap();
+        assert_eq!("Labels", document["kind"]);
+        assert_eq!(4, document["updates"].as_array().unwrap().len());
+
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains("Label created"), "{}", stderr);
+    }
+
+    #[tokio::test]
+    async fn templates() {
+        let configuration: Configuration = serde_yml::from_str(
+            r#"
+repos:
+  - org: org
+    repo: repo
+labels:
+  - name: cherry-pick/release-0.3
+    color: 000000
+milestones: []
+label_templates:
+  - name: cherry-pick/{branch}
+    color: fef2a0
+    description: Cherry-pick to {branch}.
+    branches: release-*
+"#,
+        )
+        .unwrap();
+        let client = FakeGitHub::new().with_repo(
+            "org",
+            "repo",
+            FakeRepo {
+                labels: vec![
+                    label(
+                        "cherry-pick/release-0.1",
+                        "fef2a0",
+                        Some("Cherry-pick to release-0.1."),
+                    ),
+                    label("cherry-pick/release-0.2", "fef2a0", None),
+                    label("cherry-pick/release/0.2.z", "fef2a0", None),
+                ],
+                refs: ["main", "release-0.2", "release-0.3", "release-0.4"]
+                    .iter()
+                    .map(|branch| Reference {
+                        name: format!("refs/heads/{branch}"),
+                        sha: "abc".to_string(),
+                    })
+                    .collect(),
+                ..Default::default()
+            },
+        );
+
+        let labels = Labels {
+            client: &client,
+            configuration: &configuration,
+            prune: false,
+        };
+
+        let plan = Plan::compute(&labels, &configuration.repos).await.unwrap();
+        assert_eq!(
+            "\
+org/repo: create label \"cherry-pick/release-0.3\" (color: 000000, description: None)
+org/repo: update label \"cherry-pick/release-0.2\" (color: fef2a0 -> fef2a0, description: None -> Some(\"Cherry-pick to release-0.2.\"))
+org/repo: create label \"cherry-pick/release-0.4\" (color: fef2a0, description: Some(\"Cherry-pick to release-0.4.\"))
+org/repo: delete label \"cherry-pick/release-0.1\" (branch \"release-0.1\" is gone)",
+            plan.render(Output::Text).unwrap()
+        );
+
+        let args = PlanArgs {
+            confirm: true,
+            output: Output::Text,
+            ..Default::default()
+        };
+        run(&labels, &configuration.repos, &args).await.unwrap();
+        assert!(Plan::compute(&labels, &configuration.repos)
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            vec![
+                "cherry-pick/release-0.2",
+                "cherry-pick/release/0.2.z",
+                "cherry-pick/release-0.3",
+                "cherry-pick/release-0.4",
+            ],
+            client
+                .repo("org", "repo")
+                .labels
+                .iter()
+                .map(|l| l.name.as_str())
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,636 @@
+use std::{collections::HashMap, fmt};
+
+use action::github::{GitHub, Issue, MilestonePayload};
+use anyhow::anyhow;
+use async_trait::async_trait;
+use chrono::{NaiveDate, Utc};
+use config::types::{Configuration, Due, Milestone, OnMissing, Repo, State};
+use reconcile::{error::Result, resource::Resource};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum Why {
+    // Wanted, Issues
+    Missing(Milestone, Vec<Issue>),
+    // Wanted, CurrentNumber, Current, Issues
+    Changed(Milestone, i64, Milestone, Vec<Issue>),
+}
+
+impl fmt::Display for Why {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let issues = match self {
+            Why::Missing(wanted, issues) => {
+                write!(f, "create milestone {:?}", wanted.title)?;
+                issues
+            }
+            Why::Changed(wanted, number, _current, issues) => {
+                write!(f, "update milestone {:?} (#{})", wanted.title, number)?;
+                issues
+            }
+        };
+
+        if !issues.is_empty() {
+            let numbers: Vec<String> = issues.iter().map(|i| format!("#{}", i.number)).collect();
+            write!(f, ", move {}", numbers.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+// Observed holds the milestones of a repository, and the open issues of the
+// milestones being replaced.
+#[derive(Serialize, Deserialize, PartialEq)]
+pub struct Observed {
+    milestones: HashMap<String, action::github::Milestone>,
+
+    // Replaced milestone title -> open issues, a closed milestone is still
+    // searched as it may have been closed before its issues were moved
+    issues: HashMap<String, Vec<Issue>>,
+
+    // Replaced milestones that never existed in the repository
+    missing: Vec<String>,
+
+    // Replaced milestone title -> due date, relative due dates count from it
+    replaced_due: HashMap<String, NaiveDate>,
+}
+
+// Milestones reconciles the milestones of a repository with
+// `Configuration.milestones`.
+pub struct Milestones<'a> {
+    pub client: &'a dyn GitHub,
+    pub configuration: &'a Configuration,
+}
+
+fn payload(milestone: &Milestone) -> MilestonePayload {
+    MilestonePayload {
+        title: milestone.title.clone(),
+        description: milestone.description.clone(),
+        state: milestone.state.map(String::from),
+        due_on: milestone
+            .due
+            .map(|due| due_on(due.resolve(Utc::now().date_naive()))),
+    }
+}
+
+// due_on is the timestamp sent for a due date. GitHub only keeps the date, noon
+// UTC is the same date in every timezone the UI might show it in. The diff
+// compares `due_on` by its UTC date, which is what this round-trips to.
+fn due_on(date: NaiveDate) -> String {
+    format!("{}T12:00:00Z", date.format("%Y-%m-%d"))
+}
+
+#[async_trait]
+impl Resource for Milestones<'_> {
+    const KIND: &'static str = "Milestones";
+
+    type Desired = Milestone;
+    type Observed = Observed;
+    type Change = Why;
+
+    fn desired(&self, repo: &Repo) -> Vec<Milestone> {
+        self.configuration.milestones_for(repo)
+    }
+
+    async fn observe(&self, org: &str, repo: &str) -> Result<Observed> {
+        // Closed milestones are observed too, a milestone closed by
+        // `state: closed` still exists and GitHub rejects creating it again.
+        // An open one wins over a closed one with the same title.
+        let closed_milestones = self.client.list_closed_milestones(org, repo).await?;
+        let open_milestones = self.client.list_milestones(org, repo).await?;
+
+        let current_milestones_map: HashMap<String, action::github::Milestone> = closed_milestones
+            .into_iter()
+            .chain(open_milestones)
+            .map(|m| (m.title.clone(), m))
+            .collect();
+
+        let desired = self
+            .configuration
+            .find_repo(org, repo)
+            .map(|r| self.desired(r))
+            .unwrap_or_default();
+
+        // Fetch the open issues of the milestones being replaced
+        let mut issues: HashMap<String, Vec<Issue>> = HashMap::new();
+        let mut missing: Vec<String> = vec![];
+        let mut replaced_due: HashMap<String, NaiveDate> = HashMap::new();
+        for want_milestone in &desired {
+            let Some(replaces) = &want_milestone.replaces else {
+                continue;
+            };
+
+            let Some(old_milestone) = current_milestones_map.get(replaces) else {
+                if want_milestone.on_missing == OnMissing::Fail {
+                    return Err(anyhow!(
+                        "{}/{}: milestone {:?} replaces {:?} which does not exist",
+                        org,
+                        repo,
+                        want_milestone.title,
+                        replaces
+                    )
+                    .into());
+                }
+                missing.push(replaces.clone());
+                continue;
+            };
+
+            if let Some(due_on) = old_milestone.due_on {
+                replaced_due.insert(replaces.clone(), due_on.date_naive());
+            }
+
+            let mut repo_issues: Vec<Issue> = vec![];
+            if let Some(open_issues) = old_milestone.open_issues {
+                if open_issues > 0 {
+                    repo_issues = self
+                        .client
+                        .list_milestone_issues(org, repo, old_milestone.number)
+                        .await?;
+                }
+            }
+
+            issues.insert(replaces.clone(), repo_issues);
+        }
+
+        Ok(Observed {
+            milestones: current_milestones_map,
+            issues,
+            missing,
+            replaced_due,
+        })
+    }
+
+    fn diff(&self, desired: &[Milestone], observed: &Observed) -> Vec<Why> {
+        let mut updates: Vec<Why> = vec![];
+
+        // Compare milestones
+        for want_milestone in desired {
+            let repo_issues = want_milestone
+                .replaces
+                .as_ref()
+                .and_then(|replaces| observed.issues.get(replaces))
+                .cloned()
+                .unwrap_or_default();
+
+            // Relative due dates count from the replaced milestone, or from
+            // today when creating the milestone, otherwise they are left alone.
+            let base = want_milestone
+                .replaces
+                .as_ref()
+                .and_then(|replaces| observed.replaced_due.get(replaces))
+                .copied();
+
+            match observed.milestones.get(&want_milestone.title) {
+                None => {
+                    let base = base.unwrap_or(Utc::now().date_naive());
+                    let wanted = Milestone {
+                        due: want_milestone.due.map(|due| Due::Date(due.resolve(base))),
+                        ..want_milestone.clone()
+                    };
+                    updates.push(Why::Missing(wanted, repo_issues));
+                }
+                Some(existing_milestone) => {
+                    let existing_milestone_due =
+                        existing_milestone.due_on.map(|date| date.date_naive());
+                    let want_milestone_due = match (want_milestone.due, base) {
+                        (Some(Due::Date(date)), _) => Some(date),
+                        (Some(due), Some(base)) => Some(due.resolve(base)),
+                        _ => None,
+                    };
+
+                    let existing_milestone_state = existing_milestone
+                        .state
+                        .as_deref()
+                        .and_then(|state| state.parse::<State>().ok());
+
+                    // An unspecified due date or state is not a change, the
+                    // payload leaves them alone.
+                    let due_changed =
+                        want_milestone_due.is_some_and(|due| existing_milestone_due != Some(due));
+                    let state_changed = want_milestone
+                        .state
+                        .is_some_and(|state| existing_milestone_state != Some(state));
+
+                    if existing_milestone.description != want_milestone.description
+                        || due_changed
+                        || state_changed
+                        || !repo_issues.is_empty()
+                    {
+                        updates.push(Why::Changed(
+                            Milestone {
+                                due: want_milestone_due.map(Due::Date),
+                                ..want_milestone.clone()
+                            },
+                            existing_milestone.number,
+                            Milestone {
+                                title: existing_milestone.title.clone(),
+                                description: existing_milestone.description.clone(),
+                                state: existing_milestone_state,
+                                due: existing_milestone_due.map(Due::Date),
+                                replaces: None,
+                                on_missing: Default::default(),
+                            },
+                            repo_issues,
+                        ));
+                    };
+                }
+            };
+        }
+
+        updates
+    }
+
+    fn warnings(&self, desired: &[Milestone], observed: &Observed) -> Vec<String> {
+        desired
+            .iter()
+            .filter(|m| m.on_missing == OnMissing::Warn)
+            .filter_map(|m| {
+                let replaces = m.replaces.as_ref()?;
+                observed.missing.contains(replaces).then(|| {
+                    format!(
+                        "milestone {:?} replaces {:?} which does not exist, nothing to move",
+                        m.title, replaces
+                    )
+                })
+            })
+            .collect()
+    }
+
+    async fn apply(&self, org: &str, repo: &str, change: &Why) -> Result<()> {
+        let (milestone, issues) = match change {
+            Why::Missing(wanted_milestone, issues) => {
+                let resp = self
+                    .client
+                    .create_milestone(org, repo, &payload(wanted_milestone))
+                    .await?;
+                eprintln!("Milestone created: {:?}", resp);
+                (resp, issues)
+            }
+            Why::Changed(wanted_milestone, current_number, _current_milestone, issues) => {
+                let resp = self
+                    .client
+                    .update_milestone(org, repo, *current_number, &payload(wanted_milestone))
+                    .await?;
+                eprintln!("Milestone updated: {:?}", resp);
+                (resp, issues)
+            }
+        };
+
+        for issue in issues {
+            self.client
+                .set_issue_milestone(org, repo, issue.number, Some(milestone.number))
+                .await?;
+            eprintln!(
+                "Issue added to milestone org: {:?} repo: {:?} issue: {:?} milestone: {:?}",
+                org, repo, issue.number, milestone.number,
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use action::{
+        fake::{FakeGitHub, FakeRepo},
+        github::{Issue, Milestone},
+    };
+    use config::types::{Configuration, OnMissing};
+    use reconcile::{
+        cli::{Output, PlanArgs},
+        plan::{run, Plan},
+    };
+
+    use tempfile::NamedTempFile;
+
+    use crate::milestones::Milestones;
+
+    const CONFIG: &str = r#"
+repos:
+  - org: org
+    repo: repo
+labels: []
+milestones:
+  - title: Trustify 2.0.0
+    description: The v0.2.0 release of Trustify
+    state: open
+  - title: Trustify 2.1.0
+    description: Bucket for work we want to accomplish in the next release
+    state: open
+    replaces: Trustify 2.0.x
+"#;
+
+    // load reads the configuration the way the binary does, validating it
+    fn load(source: &str) -> Configuration {
+        let file = NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), source).unwrap();
+        Configuration::from_path(file.path().to_str().unwrap()).unwrap()
+    }
+
+    fn milestone(number: i64, title: &str, description: &str) -> Milestone {
+        Milestone {
+            number,
+            title: title.to_string(),
+            description: Some(description.to_string()),
+            state: Some("open".to_string()),
+            due_on: None,
+            open_issues: Some(0),
+        }
+    }
+
+    fn issue(number: u64, milestone: i64, pull_request: bool) -> Issue {
+        Issue {
+            number,
+            title: format!("Issue {number}"),
+            state: "open".to_string(),
+            milestone: Some(milestone),
+            pull_request,
+        }
+    }
+
+    fn client() -> FakeGitHub {
+        FakeGitHub::new().with_repo(
+            "org",
+            "repo",
+            FakeRepo {
+                milestones: vec![
+                    milestone(1, "Trustify 2.0.0", "The first release"),
+                    Milestone {
+                        open_issues: Some(2),
+                        ..milestone(2, "Trustify 2.0.x", "Patch releases")
+                    },
+                ],
+                issues: vec![issue(10, 2, false), issue(11, 2, true)],
+                ..Default::default()
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn diff() {
+        let configuration: Configuration = serde_yml::from_str(CONFIG).unwrap();
+        let client = client();
+
+        let milestones = Milestones {
+            client: &client,
+            configuration: &configuration,
+        };
+
+        let plan = Plan::compute(&milestones, &configuration.repos)
+            .await
+            .unwrap();
+        assert_eq!(
+            "\
+org/repo: update milestone \"Trustify 2.0.0\" (#1)
+org/repo: create milestone \"Trustify 2.1.0\", move #10 #11",
+            plan.render(Output::Text).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn apply() {
+        let configuration: Configuration = serde_yml::from_str(CONFIG).unwrap();
+        let client = client();
+
+        let milestones = Milestones {
+            client: &client,
+            configuration: &configuration,
+        };
+
+        let args = PlanArgs {
+            confirm: true,
+            output: Output::Text,
+            ..Default::default()
+        };
+        run(&milestones, &configuration.repos, &args).await.unwrap();
+
+        let repo = client.repo("org", "repo");
+        let created = repo
+            .milestones
+            .iter()
+            .find(|m| m.title == "Trustify 2.1.0")
+            .unwrap();
+        assert_eq!(
+            vec![Some(created.number), Some(created.number)],
+            repo.issues.iter().map(|i| i.milestone).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            Some("The v0.2.0 release of Trustify".to_string()),
+            repo.milestones[0].description
+        );
+        assert!(Plan::compute(&milestones, &configuration.repos)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn replaces_missing() {
+        let configuration: Configuration = serde_yml::from_str(
+            r#"
+repos:
+  - org: org
+    repo: closed
+  - org: org
+    repo: new
+labels: []
+milestones:
+  - title: Trustify 2.1.0
+    description: Bucket for work we want to accomplish in the next release
+    state: open
+    replaces: Trustify 2.0.x
+"#,
+        )
+        .unwrap();
+        let client = FakeGitHub::new()
+            .with_repo(
+                "org",
+                "closed",
+                FakeRepo {
+                    milestones: vec![Milestone {
+                        state: Some("closed".to_string()),
+                        open_issues: Some(1),
+                        ..milestone(2, "Trustify 2.0.x", "Patch releases")
+                    }],
+                    issues: vec![issue(10, 2, false)],
+                    ..Default::default()
+                },
+            )
+            .with_repo("org", "new", FakeRepo::default());
+
+        let milestones = Milestones {
+            client: &client,
+            configuration: &configuration,
+        };
+
+        let plan = Plan::compute(&milestones, &configuration.repos)
+            .await
+            .unwrap();
+        assert_eq!(
+            "\
+org/closed: create milestone \"Trustify 2.1.0\", move #10
+org/new: create milestone \"Trustify 2.1.0\"",
+            plan.render(Output::Text).unwrap()
+        );
+        assert_eq!(
+            vec![(
+                "new",
+                "milestone \"Trustify 2.1.0\" replaces \"Trustify 2.0.x\" which does not exist, nothing to move"
+            )],
+            plan.warnings
+                .iter()
+                .map(|w| (w.repo.as_str(), w.why.as_str()))
+                .collect::<Vec<_>>()
+        );
+
+        let mut configuration = configuration;
+        configuration.milestones[0].on_missing = OnMissing::Skip;
+        let milestones = Milestones {
+            client: &client,
+            configuration: &configuration,
+        };
+        let plan = Plan::compute(&milestones, &configuration.repos)
+            .await
+            .unwrap();
+        assert_eq!(2, plan.updates.len());
+        assert!(plan.warnings.is_empty());
+
+        configuration.milestones[0].on_missing = OnMissing::Fail;
+        let milestones = Milestones {
+            client: &client,
+            configuration: &configuration,
+        };
+        assert!(Plan::compute(&milestones, &configuration.repos)
+            .await
+            .is_err());
+        assert!(client.repo("org", "new").milestones.is_empty());
+    }
+
+    #[tokio::test]
+    async fn due() {
+        let configuration = load(
+            r#"
+repos:
+  - org: org
+    repo: repo
+labels: []
+milestones:
+  - title: Trustify 2.0.0
+    description: The v0.2.0 release of Trustify
+    state: open
+    due: 2026-11-30
+  - title: Trustify 2.1.0
+    description: Bucket for work we want to accomplish in the next release
+    state: open
+    due: +6w
+    replaces: Trustify 2.0.0
+"#,
+        );
+        let due_on = |value: &str| Some(value.parse().unwrap());
+        let client = FakeGitHub::new().with_repo(
+            "org",
+            "repo",
+            FakeRepo {
+                milestones: vec![Milestone {
+                    due_on: due_on("2026-11-30T08:00:00Z"),
+                    ..milestone(1, "Trustify 2.0.0", "The v0.2.0 release of Trustify")
+                }],
+                ..Default::default()
+            },
+        );
+
+        let milestones = Milestones {
+            client: &client,
+            configuration: &configuration,
+        };
+
+        let plan = Plan::compute(&milestones, &configuration.repos)
+            .await
+            .unwrap();
+        assert_eq!(
+            "org/repo: create milestone \"Trustify 2.1.0\"",
+            plan.render(Output::Text).unwrap()
+        );
+
+        let args = PlanArgs {
+            confirm: true,
+            output: Output::Text,
+            ..Default::default()
+        };
+        run(&milestones, &configuration.repos, &args).await.unwrap();
+
+        assert_eq!(
+            due_on("2027-01-11T12:00:00Z"),
+            client.repo("org", "repo").milestones[1].due_on
+        );
+        assert!(Plan::compute(&milestones, &configuration.repos)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn state() {
+        let configuration: Configuration = serde_yml::from_str(
+            r#"
+repos:
+  - org: org
+    repo: repo
+labels: []
+milestones:
+  - title: Trustify 2.0.0
+    description: The v0.2.0 release of Trustify
+    state: Closed
+  - title: Trustify 2.1.0
+    description: Bucket for work we want to accomplish in the next release
+"#,
+        )
+        .unwrap();
+        let client = FakeGitHub::new().with_repo(
+            "org",
+            "repo",
+            FakeRepo {
+                milestones: vec![
+                    milestone(1, "Trustify 2.0.0", "The v0.2.0 release of Trustify"),
+                    milestone(
+                        2,
+                        "Trustify 2.1.0",
+                        "Bucket for work we want to accomplish in the next release",
+                    ),
+                ],
+                ..Default::default()
+            },
+        );
+
+        let milestones = Milestones {
+            client: &client,
+            configuration: &configuration,
+        };
+
+        let plan = Plan::compute(&milestones, &configuration.repos)
+            .await
+            .unwrap();
+        assert_eq!(
+            "org/repo: update milestone \"Trustify 2.0.0\" (#1)",
+            plan.render(Output::Text).unwrap()
+        );
+
+        let args = PlanArgs {
+            confirm: true,
+            output: Output::Text,
+            ..Default::default()
+        };
+        run(&milestones, &configuration.repos, &args).await.unwrap();
+
+        assert_eq!(
+            Some("closed".to_string()),
+            client.repo("org", "repo").milestones[0].state
+        );
+
+        // The closed milestone is still observed, it is not created again
+        assert!(Plan::compute(&milestones, &configuration.repos)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
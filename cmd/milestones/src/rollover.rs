@@ -0,0 +1,392 @@
+use std::{collections::BTreeMap, fmt, sync::Mutex};
+
+use action::{
+    commands::append_to,
+    github::{GitHub, Issue, Milestone, MilestonePayload},
+};
+use anyhow::anyhow;
+use async_trait::async_trait;
+use config::types::Repo;
+use reconcile::{error::Result, plan::Update, resource::Resource};
+use serde::{Deserialize, Serialize};
+
+#[derive(clap::Args, Debug)]
+pub struct RolloverArgs {
+    /// Title of the milestone to move from, e.g. "Trustify 2.0.0"
+    #[arg(long)]
+    pub from: String,
+
+    /// Title of the milestone to move to, it is created when missing
+    #[arg(long)]
+    pub to: String,
+
+    /// Close the milestone moved from once it is empty
+    #[arg(long, default_value = "false")]
+    pub close: bool,
+
+    /// Append a markdown summary of what was moved to this file, e.g. $GITHUB_STEP_SUMMARY
+    #[arg(long, default_value = "")]
+    pub summary: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum Why {
+    // To
+    Missing(String),
+    // From, To, Issues
+    Moved(String, String, Vec<Issue>),
+    // Number, From
+    Closed(i64, String),
+}
+
+impl fmt::Display for Why {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Why::Missing(to) => write!(f, "create milestone {:?}", to),
+            Why::Moved(from, to, issues) => {
+                let numbers: Vec<String> =
+                    issues.iter().map(|i| format!("#{}", i.number)).collect();
+                write!(f, "move {} from {:?} to {:?}", numbers.join(" "), from, to)
+            }
+            Why::Closed(number, from) => write!(f, "close milestone {:?} (#{})", from, number),
+        }
+    }
+}
+
+// Observed holds both milestones, open or closed, and the open issues and pull
+// requests of the one being moved from.
+#[derive(Serialize, Deserialize, PartialEq)]
+pub struct Observed {
+    from: Option<Milestone>,
+    to: Option<Milestone>,
+    issues: Vec<Issue>,
+}
+
+// Rollover moves the open issues and pull requests of a milestone to the next
+// one in every repository, e.g. when a release is cut.
+pub struct Rollover<'a> {
+    pub client: &'a dyn GitHub,
+    pub from: String,
+    pub to: String,
+    pub close: bool,
+
+    // Applied records the changes made, for the summary
+    pub applied: Mutex<Vec<Update<Why>>>,
+}
+
+impl Rollover<'_> {
+    async fn find_milestone(
+        &self,
+        org: &str,
+        repo: &str,
+        title: &str,
+    ) -> Result<Option<Milestone>> {
+        let open = self.client.list_milestones(org, repo).await?;
+        if let Some(milestone) = open.into_iter().find(|m| m.title == title) {
+            return Ok(Some(milestone));
+        }
+
+        let closed = self.client.list_closed_milestones(org, repo).await?;
+        Ok(closed.into_iter().find(|m| m.title == title))
+    }
+
+    // summary renders what was applied as markdown, one row per repository.
+    pub fn summary(&self) -> String {
+        #[derive(Default)]
+        struct Row {
+            issues: Vec<String>,
+            pull_requests: Vec<String>,
+            closed: bool,
+        }
+
+        let applied = self.applied.lock().unwrap();
+        let mut rows: BTreeMap<String, Row> = BTreeMap::new();
+        for update in applied.iter() {
+            let row = rows
+                .entry(format!("{}/{}", update.org, update.repo))
+                .or_default();
+            match &update.why {
+                Why::Missing(_) => {}
+                Why::Moved(_, _, issues) => {
+                    for issue in issues {
+                        match issue.pull_request {
+                            true => row.pull_requests.push(format!("#{}", issue.number)),
+                            false => row.issues.push(format!("#{}", issue.number)),
+                        }
+                    }
+                }
+                Why::Closed(_, _) => row.closed = true,
+            }
+        }
+
+        let mut summary = format!("## Rollover from {:?} to {:?}\n\n", self.from, self.to);
+        if rows.is_empty() {
+            summary.push_str("Nothing was moved.\n");
+            return summary;
+        }
+
+        summary.push_str("| Repository | Issues | Pull requests | Closed |\n");
+        summary.push_str("| --- | --- | --- | --- |\n");
+        for (repo, row) in rows {
+            summary.push_str(&format!(
+                "| {} | {} | {} | {} |\n",
+                repo,
+                row.issues.join(" "),
+                row.pull_requests.join(" "),
+                if row.closed { "yes" } else { "no" }
+            ));
+        }
+        summary
+    }
+
+    // write_summary appends the summary to the file, GitHub renders
+    // $GITHUB_STEP_SUMMARY on the workflow run page.
+    pub fn write_summary(&self, path: &str) -> Result<()> {
+        append_to(path, &self.summary())?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Resource for Rollover<'_> {
+    const KIND: &'static str = "Rollover";
+
+    // Title of the milestone to move to
+    type Desired = String;
+    type Observed = Observed;
+    type Change = Why;
+
+    fn desired(&self, _repo: &Repo) -> Vec<String> {
+        vec![self.to.clone()]
+    }
+
+    async fn observe(&self, org: &str, repo: &str) -> Result<Observed> {
+        let from = self.find_milestone(org, repo, &self.from).await?;
+        let to = self.find_milestone(org, repo, &self.to).await?;
+
+        let mut issues: Vec<Issue> = vec![];
+        if let Some(from) = &from {
+            if from.open_issues.unwrap_or_default() > 0 {
+                issues = self
+                    .client
+                    .list_milestone_issues(org, repo, from.number)
+                    .await?;
+            }
+        }
+
+        Ok(Observed { from, to, issues })
+    }
+
+    fn diff(&self, desired: &[String], observed: &Observed) -> Vec<Why> {
+        let mut updates: Vec<Why> = vec![];
+
+        let Some(from) = &observed.from else {
+            return updates;
+        };
+
+        for to in desired {
+            if observed.to.is_none() {
+                updates.push(Why::Missing(to.clone()));
+            }
+            if !observed.issues.is_empty() {
+                updates.push(Why::Moved(
+                    from.title.clone(),
+                    to.clone(),
+                    observed.issues.clone(),
+                ));
+            }
+        }
+
+        if self.close && from.state.as_deref() != Some("closed") {
+            updates.push(Why::Closed(from.number, from.title.clone()));
+        }
+
+        updates
+    }
+
+    fn warnings(&self, _desired: &[String], observed: &Observed) -> Vec<String> {
+        match observed.from {
+            Some(_) => vec![],
+            None => vec![format!("milestone {:?} does not exist", self.from)],
+        }
+    }
+
+    async fn apply(&self, org: &str, repo: &str, change: &Why) -> Result<()> {
+        match change {
+            Why::Missing(to) => {
+                let resp = self
+                    .client
+                    .create_milestone(
+                        org,
+                        repo,
+                        &MilestonePayload {
+                            title: to.clone(),
+                            description: None,
+                            state: None,
+                            due_on: None,
+                        },
+                    )
+                    .await?;
+                eprintln!("Milestone created: {:?}", resp);
+            }
+            Why::Moved(_from, to, issues) => {
+                let milestone = self
+                    .find_milestone(org, repo, to)
+                    .await?
+                    .ok_or_else(|| anyhow!("{}/{}: milestone {:?} not found", org, repo, to))?;
+
+                for issue in issues {
+                    self.client
+                        .set_issue_milestone(org, repo, issue.number, Some(milestone.number))
+                        .await?;
+                    eprintln!(
+                        "Issue added to milestone org: {:?} repo: {:?} issue: {:?} milestone: {:?}",
+                        org, repo, issue.number, milestone.number,
+                    );
+                }
+            }
+            Why::Closed(number, from) => {
+                let resp = self
+                    .client
+                    .update_milestone(
+                        org,
+                        repo,
+                        *number,
+                        &MilestonePayload {
+                            title: from.clone(),
+                            description: None,
+                            state: Some("closed".to_string()),
+                            due_on: None,
+                        },
+                    )
+                    .await?;
+                eprintln!("Milestone closed: {:?}", resp);
+            }
+        };
+
+        self.applied.lock().unwrap().push(Update {
+            org: org.to_string(),
+            repo: repo.to_string(),
+            why: change.clone(),
+        });
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use action::{
+        fake::{FakeGitHub, FakeRepo},
+        github::{Issue, Milestone},
+    };
+    use reconcile::{
+        cli::Output,
+        plan::Plan,
+        testing::{apply_and_recompute, repos},
+    };
+
+    use crate::rollover::Rollover;
+
+    fn milestone(number: i64, title: &str, open_issues: i64) -> Milestone {
+        Milestone {
+            number,
+            title: title.to_string(),
+            description: None,
+            state: Some("open".to_string()),
+            due_on: None,
+            open_issues: Some(open_issues),
+        }
+    }
+
+    fn issue(number: u64, milestone: i64, pull_request: bool) -> Issue {
+        Issue {
+            number,
+            title: format!("Issue {number}"),
+            state: "open".to_string(),
+            milestone: Some(milestone),
+            pull_request,
+        }
+    }
+
+    #[tokio::test]
+    async fn rollover() {
+        let client = FakeGitHub::new()
+            .with_repo(
+                "org",
+                "server",
+                FakeRepo {
+                    milestones: vec![
+                        milestone(1, "Trustify 2.0.0", 2),
+                        milestone(2, "Trustify 2.1.0", 0),
+                    ],
+                    issues: vec![
+                        issue(10, 1, false),
+                        issue(11, 1, true),
+                        Issue {
+                            state: "closed".to_string(),
+                            ..issue(12, 1, false)
+                        },
+                    ],
+                    ..Default::default()
+                },
+            )
+            .with_repo(
+                "org",
+                "ui",
+                FakeRepo {
+                    milestones: vec![milestone(1, "Trustify 2.0.0", 1)],
+                    issues: vec![issue(20, 1, true)],
+                    ..Default::default()
+                },
+            )
+            .with_repo("org", "new", FakeRepo::default());
+        let repos = repos(&["server", "ui", "new"]);
+
+        let rollover = Rollover {
+            client: &client,
+            from: "Trustify 2.0.0".to_string(),
+            to: "Trustify 2.1.0".to_string(),
+            close: true,
+            applied: Mutex::new(vec![]),
+        };
+
+        let plan = Plan::compute(&rollover, &repos).await.unwrap();
+        assert_eq!(
+            "\
+org/server: move #10 #11 from \"Trustify 2.0.0\" to \"Trustify 2.1.0\"
+org/server: close milestone \"Trustify 2.0.0\" (#1)
+org/ui: create milestone \"Trustify 2.1.0\"
+org/ui: move #20 from \"Trustify 2.0.0\" to \"Trustify 2.1.0\"
+org/ui: close milestone \"Trustify 2.0.0\" (#1)",
+            plan.render(Output::Text).unwrap()
+        );
+        assert_eq!(1, plan.warnings.len());
+
+        assert_eq!("", apply_and_recompute(&rollover, &repos).await);
+
+        let server = client.repo("org", "server");
+        assert_eq!(
+            vec![Some(2), Some(2), Some(1)],
+            server
+                .issues
+                .iter()
+                .map(|i| i.milestone)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(Some("closed".to_string()), server.milestones[0].state);
+        assert_eq!(
+            "\
+## Rollover from \"Trustify 2.0.0\" to \"Trustify 2.1.0\"
+
+| Repository | Issues | Pull requests | Closed |
+| --- | --- | --- | --- |
+| org/server | #10 | #11 | yes |
+| org/ui |  | #20 | yes |
+",
+            rollover.summary()
+        );
+    }
+}
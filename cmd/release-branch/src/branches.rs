@@ -0,0 +1,248 @@
+use std::{collections::HashMap, fmt};
+
+use action::github::{GitHub, Reference};
+use async_trait::async_trait;
+use config::types::Repo;
+use reconcile::{error::Result, resource::Resource};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Branch {
+    pub name: String,
+    // Branch, tag or SHA the branch is created from
+    pub base: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum Why {
+    // Wanted, SHA of the base
+    Missing(Branch, String),
+}
+
+impl fmt::Display for Why {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Why::Missing(wanted, sha) => write!(
+                f,
+                "create branch {:?} from {} ({})",
+                wanted.name, wanted.base, sha
+            ),
+        }
+    }
+}
+
+// Observed holds the release branch, when it already exists, and the commit
+// its base points to.
+#[derive(Serialize, Deserialize, PartialEq)]
+pub struct Observed {
+    branch: Option<Reference>,
+    base: Option<String>,
+}
+
+// Branches creates a release branch in every repository that does not have
+// it yet, existing branches are never moved.
+pub struct Branches<'a> {
+    pub client: &'a dyn GitHub,
+    pub branch: String,
+    pub base: String,
+
+    // Overrides of the base by `org/repo`, lowercase
+    pub overrides: HashMap<String, String>,
+}
+
+impl Branches<'_> {
+    fn base_for(&self, org: &str, repo: &str) -> &str {
+        self.overrides
+            .get(&format!("{org}/{repo}").to_lowercase())
+            .unwrap_or(&self.base)
+    }
+}
+
+#[async_trait]
+impl Resource for Branches<'_> {
+    const KIND: &'static str = "ReleaseBranch";
+
+    type Desired = Branch;
+    type Observed = Observed;
+    type Change = Why;
+
+    fn desired(&self, repo: &Repo) -> Vec<Branch> {
+        vec![Branch {
+            name: self.branch.clone(),
+            base: self.base_for(&repo.org, &repo.repo).to_string(),
+        }]
+    }
+
+    async fn observe(&self, org: &str, repo: &str) -> Result<Observed> {
+        let branch = self
+            .client
+            .get_ref(org, repo, &format!("heads/{}", self.branch))
+            .await?;
+
+        let mut base = None;
+        if branch.is_none() {
+            base = self
+                .client
+                .resolve_commit(org, repo, self.base_for(org, repo))
+                .await?;
+        }
+
+        Ok(Observed { branch, base })
+    }
+
+    fn diff(&self, desired: &[Branch], observed: &Observed) -> Vec<Why> {
+        if observed.branch.is_some() {
+            return vec![];
+        }
+        let Some(sha) = &observed.base else {
+            return vec![];
+        };
+
+        desired
+            .iter()
+            .map(|wanted| Why::Missing(wanted.clone(), sha.clone()))
+            .collect()
+    }
+
+    fn warnings(&self, desired: &[Branch], observed: &Observed) -> Vec<String> {
+        if observed.branch.is_some() || observed.base.is_some() {
+            return vec![];
+        }
+
+        desired
+            .iter()
+            .map(|wanted| {
+                format!(
+                    "base {:?} of branch {:?} does not exist",
+                    wanted.base, wanted.name
+                )
+            })
+            .collect()
+    }
+
+    async fn apply(&self, org: &str, repo: &str, change: &Why) -> Result<()> {
+        match change {
+            Why::Missing(wanted, sha) => {
+                let resp = self
+                    .client
+                    .create_ref(
+                        org,
+                        repo,
+                        &Reference {
+                            name: format!("refs/heads/{}", wanted.name),
+                            sha: sha.clone(),
+                        },
+                    )
+                    .await?;
+                eprintln!("Branch created: {:?}", resp);
+            }
+        };
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use action::{
+        fake::{FakeGitHub, FakeRepo},
+        github::Reference,
+    };
+    use config::types::Repo;
+    use reconcile::{
+        cli::{Output, PlanArgs},
+        plan::{run, Plan},
+    };
+
+    use crate::branches::Branches;
+
+    fn reference(name: &str, sha: &str) -> Reference {
+        Reference {
+            name: name.to_string(),
+            sha: sha.to_string(),
+        }
+    }
+
+    fn repo(name: &str) -> Repo {
+        Repo {
+            org: "org".to_string(),
+            repo: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn client() -> FakeGitHub {
+        FakeGitHub::new()
+            .with_repo(
+                "org",
+                "server",
+                FakeRepo {
+                    refs: vec![
+                        reference("refs/heads/main", "aaa"),
+                        reference("refs/tags/v0.3.0-alpha.1", "bbb"),
+                    ],
+                    ..Default::default()
+                },
+            )
+            .with_repo(
+                "org",
+                "ui",
+                FakeRepo {
+                    refs: vec![
+                        reference("refs/heads/main", "ccc"),
+                        reference("refs/heads/release-0.3", "ddd"),
+                    ],
+                    ..Default::default()
+                },
+            )
+            .with_repo(
+                "org",
+                "operator",
+                FakeRepo {
+                    refs: vec![reference("refs/heads/master", "eee")],
+                    ..Default::default()
+                },
+            )
+    }
+
+    #[tokio::test]
+    async fn create() {
+        let client = client();
+        let repos = vec![repo("server"), repo("ui"), repo("operator")];
+
+        let branches = Branches {
+            client: &client,
+            branch: "release-0.3".to_string(),
+            base: "main".to_string(),
+            overrides: HashMap::from([("org/server".to_string(), "v0.3.0-alpha.1".to_string())]),
+        };
+
+        let plan = Plan::compute(&branches, &repos).await.unwrap();
+        assert_eq!(
+            "org/server: create branch \"release-0.3\" from v0.3.0-alpha.1 (bbb)",
+            plan.render(Output::Text).unwrap()
+        );
+        assert_eq!(1, plan.warnings.len());
+        assert_eq!("org", plan.warnings[0].org);
+        assert_eq!("operator", plan.warnings[0].repo);
+
+        let args = PlanArgs {
+            confirm: true,
+            output: Output::Text,
+            ..Default::default()
+        };
+        run(&branches, &repos, &args).await.unwrap();
+
+        assert_eq!(
+            Some(&reference("refs/heads/release-0.3", "bbb")),
+            client.repo("org", "server").refs.last()
+        );
+        assert_eq!(
+            Some(&reference("refs/heads/release-0.3", "ddd")),
+            client.repo("org", "ui").refs.last()
+        );
+        assert!(Plan::compute(&branches, &repos).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,22 @@
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("{0:?} is not a release branch, expected release-X.Y, see VERSIONING.md")]
+    InvalidBranch(String),
+
+    #[error("{0:?} is not a base override, expected org/repo=ref")]
+    InvalidBase(String),
+
+    #[error(transparent)]
+    Config(#[from] config::error::Error),
+
+    #[error(transparent)]
+    Action(#[from] action::error::Error),
+
+    #[error(transparent)]
+    Reconcile(#[from] reconcile::error::Error),
+
+    #[error(transparent)]
+    Any(#[from] anyhow::Error),
+}
@@ -0,0 +1,86 @@
+use std::{
+    collections::HashMap,
+    process::{ExitCode, Termination},
+};
+
+use action::client;
+use branches::Branches;
+use clap::Parser;
+use reconcile::cli::PlanArgs;
+use version::branch::Branch;
+
+use crate::error::Error;
+
+mod branches;
+mod error;
+
+#[derive(clap::Parser, Debug)]
+#[command(
+    author,
+    version = env ! ("CARGO_PKG_VERSION"),
+    long_about = None
+)]
+struct Cli {
+    /// Path to config.yaml
+    #[arg(long, default_value = "")]
+    config: String,
+
+    /// Name of the release branch, e.g. release-0.3
+    #[arg(long)]
+    branch: String,
+
+    /// Branch, tag or SHA the release branch is created from
+    #[arg(long, default_value = "main")]
+    base: String,
+
+    /// Base of a single repository, e.g. trustificationdemo/trustify=v0.3.0-alpha.2, can be repeated
+    #[arg(long = "base-override")]
+    base_overrides: Vec<String>,
+
+    #[command(flatten)]
+    plan: PlanArgs,
+}
+
+#[tokio::main]
+async fn main() -> impl Termination {
+    match exec().await {
+        Ok(code) => code,
+        Err(error) => {
+            println!("{:#?}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn exec() -> crate::error::Result<ExitCode> {
+    let cli = Cli::parse();
+
+    if !matches!(cli.branch.parse(), Ok(Branch::Release { .. })) {
+        return Err(Error::InvalidBranch(cli.branch));
+    }
+
+    let mut overrides: HashMap<String, String> = HashMap::new();
+    for base_override in &cli.base_overrides {
+        let (repo, base) = base_override
+            .split_once('=')
+            .filter(|(repo, base)| repo.contains('/') && !base.is_empty())
+            .ok_or_else(|| Error::InvalidBase(base_override.clone()))?;
+        overrides.insert(repo.to_lowercase(), base.to_string());
+    }
+
+    let configuration = config::types::Configuration::from_path(&cli.config)?;
+
+    // Instantiate the client and create the branch in every repo
+    let client = client::get_client()?;
+
+    let branches = Branches {
+        client: &client,
+        branch: cli.branch.clone(),
+        base: cli.base.clone(),
+        overrides,
+    };
+
+    let code = reconcile::plan::run(&branches, &configuration.repos, &cli.plan).await?;
+
+    Ok(code)
+}
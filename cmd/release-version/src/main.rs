@@ -0,0 +1,122 @@
+use std::process::{ExitCode, Termination};
+
+use action::{client, commands::set_output};
+use anyhow::anyhow;
+use clap::Parser;
+use config::types::{Configuration, TitleConvention};
+use version::{branch::Branch, version::Version};
+
+mod error;
+mod next;
+
+#[derive(clap::Parser, Debug)]
+#[command(
+    author,
+    version = env ! ("CARGO_PKG_VERSION"),
+    long_about = None
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum Command {
+    /// Check a tag follows VERSIONING.md and print what kind of release it is
+    Check(CheckArgs),
+
+    /// Print the next version of a branch from its tags and merged pull requests
+    Next(NextArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct CheckArgs {
+    /// Tag of the release (e.g. v1.2.3 or v1.2.0-alpha.2)
+    #[arg(long)]
+    tag: String,
+
+    /// Branch the release is made from (e.g. main or release-1.2), fails when it can't make this tag
+    #[arg(long, default_value = "")]
+    branch: String,
+}
+
+#[derive(clap::Args, Debug)]
+struct NextArgs {
+    /// Path to config.yaml, used to find the PR title convention of the repository
+    #[arg(long, default_value = "")]
+    config: String,
+
+    /// Repository in the form org/repo
+    #[arg(long)]
+    repository: String,
+
+    /// Branch the release is made from (e.g. main or release-1.2)
+    #[arg(long, default_value = "main")]
+    branch: String,
+}
+
+#[tokio::main]
+async fn main() -> impl Termination {
+    match exec().await {
+        Ok(_) => ExitCode::SUCCESS,
+        Err(error) => {
+            println!("{:#?}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// Results are set as step outputs, they are printed as key=value lines
+// outside of a workflow run.
+async fn exec() -> crate::error::Result<()> {
+    let cli = Cli::parse();
+
+    match cli.command {
+        Command::Check(args) => {
+            let version: Version = args.tag.parse()?;
+            if !args.branch.is_empty() {
+                args.branch.parse::<Branch>()?.check(&version)?;
+            }
+
+            set_output("is_prerelease", &version.is_prerelease().to_string())?;
+            set_output("is_dotzero", &version.is_dotzero().to_string())?;
+            set_output("xy_version", &version.xy())?;
+        }
+        Command::Next(args) => {
+            let (org, repo) = args
+                .repository
+                .split_once('/')
+                .ok_or_else(|| anyhow!("repository must be in the form org/repo"))?;
+
+            let title_convention = if args.config.is_empty() {
+                TitleConvention::default()
+            } else {
+                let configuration = Configuration::from_path(&args.config)?;
+                configuration
+                    .find_repo(org, repo)
+                    .map(|repo| repo.convention.clone())
+                    .unwrap_or_default()
+            };
+
+            let branch: Branch = args.branch.parse()?;
+            let client = client::get_client()?;
+
+            let (version, previous) = next::next_version(
+                &client,
+                title_convention.convention().as_ref(),
+                org,
+                repo,
+                &branch,
+            )
+            .await?;
+
+            set_output("version", &version.to_string())?;
+            set_output(
+                "previous",
+                &previous.map(|v| v.to_string()).unwrap_or_default(),
+            )?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,130 @@
+use action::github::GitHub;
+use pr::convention::Convention;
+use version::{
+    branch::Branch,
+    next::{next, previous, Bump},
+    version::Version,
+};
+
+// next_version computes the next version of the branch, and the tag it
+// follows, from the tags of the repository and the pull requests merged
+// since. Tags that don't follow VERSIONING.md are ignored.
+pub async fn next_version(
+    client: &dyn GitHub,
+    convention: &dyn Convention,
+    org: &str,
+    repo: &str,
+    branch: &Branch,
+) -> crate::error::Result<(Version, Option<Version>)> {
+    let tags: Vec<Version> = client
+        .list_tags(org, repo)
+        .await?
+        .iter()
+        .filter_map(|r| r.name.strip_prefix("refs/tags/"))
+        .filter_map(|name| name.parse().ok())
+        .collect();
+
+    let previous = previous(branch, &tags).cloned();
+
+    let mut bump = None;
+    if let Some(previous) = &previous {
+        let pull_requests = client
+            .list_merged_pull_requests(org, repo, &previous.to_string(), &branch.to_string())
+            .await?;
+        let pr_types: Vec<_> = pull_requests
+            .iter()
+            .filter_map(|pull_request| convention.classify(&pull_request.title))
+            .collect();
+        bump = Bump::highest(&pr_types);
+    }
+
+    let version = next(branch, &tags, bump)?;
+    Ok((version, previous))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use action::{
+        fake::{FakeGitHub, FakeRepo},
+        github::{PullRequest, Reference},
+    };
+    use pr::convention::Emoji;
+
+    use crate::next::next_version;
+
+    fn tag(name: &str) -> Reference {
+        Reference {
+            name: format!("refs/tags/{name}"),
+            sha: name.to_string(),
+        }
+    }
+
+    fn pull_request(number: u64, title: &str) -> PullRequest {
+        PullRequest {
+            number,
+            title: title.to_string(),
+            author: None,
+            url: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn next() {
+        let client = FakeGitHub::new().with_repo(
+            "org",
+            "repo",
+            FakeRepo {
+                refs: vec![
+                    tag("v1.1.0"),
+                    tag("v1.2.0-alpha.1"),
+                    tag("v1.2.0"),
+                    tag("latest"),
+                ],
+                compare: HashMap::from([
+                    (
+                        "v1.2.0...main".to_string(),
+                        vec![
+                            pull_request(1, ":bug: Fix a bug"),
+                            pull_request(2, ":warning: Drop the v1 API"),
+                        ],
+                    ),
+                    (
+                        "v1.2.0...release-1.2".to_string(),
+                        vec![pull_request(3, ":bug: Fix a bug")],
+                    ),
+                ]),
+                ..Default::default()
+            },
+        );
+
+        let (version, previous) =
+            next_version(&client, &Emoji, "org", "repo", &"main".parse().unwrap())
+                .await
+                .unwrap();
+        assert_eq!("v2.0.0-alpha.1", version.to_string());
+        assert_eq!(Some("v1.2.0".to_string()), previous.map(|v| v.to_string()));
+
+        let (version, _) = next_version(
+            &client,
+            &Emoji,
+            "org",
+            "repo",
+            &"release-1.2".parse().unwrap(),
+        )
+        .await
+        .unwrap();
+        assert_eq!("v1.2.1", version.to_string());
+
+        assert!(next_version(
+            &client,
+            &Emoji,
+            "org",
+            "repo",
+            &"release-1.1".parse().unwrap()
+        )
+        .await
+        .is_ok());
+    }
+}
@@ -0,0 +1,101 @@
+use std::{
+    fs,
+    process::{ExitCode, Termination},
+};
+
+use action::{client, context::GitHubVariables};
+use clap::Parser;
+use config::types::Repo;
+use reconcile::cli::PlanArgs;
+use serde::Deserialize;
+use triage::Triage;
+
+mod error;
+mod triage;
+
+#[derive(clap::Parser, Debug)]
+#[command(
+    author,
+    version = env ! ("CARGO_PKG_VERSION"),
+    long_about = None
+)]
+struct Cli {
+    /// Path to config.yaml
+    #[arg(long, default_value = "")]
+    config: String,
+
+    /// Login of the bot the comments are made with, comments of other users are never changed
+    #[arg(long, default_value = "trustificationdemo-ci-bot[bot]")]
+    bot: String,
+
+    #[command(flatten)]
+    plan: PlanArgs,
+}
+
+// Event is the part of the `issues` or `pull_request` event we need
+#[derive(Debug, Deserialize)]
+struct Event {
+    issue: Option<Number>,
+    pull_request: Option<Number>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Number {
+    number: u64,
+}
+
+#[tokio::main]
+async fn main() -> impl Termination {
+    match exec().await {
+        Ok(code) => code,
+        Err(error) => {
+            println!("{:#?}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn exec() -> crate::error::Result<ExitCode> {
+    let cli = Cli::parse();
+
+    let configuration = config::types::Configuration::from_path(&cli.config)?;
+
+    // Find the issue or PR of the event
+    let gh_context = GitHubVariables::from_env()?;
+    let (org, repo) = gh_context.repository().ok_or_else(|| {
+        crate::error::Error::InvalidRepository(gh_context.github_repository.clone())
+    })?;
+
+    let event_file = fs::read_to_string(&gh_context.github_event_path)?;
+    let event: Event =
+        serde_json::from_str(&event_file).map_err(|err| crate::error::Error::UnmarshalEvent {
+            file_path: gh_context.github_event_path.clone(),
+            err,
+        })?;
+    let Some(Number { number }) = event.issue.or(event.pull_request) else {
+        println!(
+            "{} event without an issue or pull request, nothing to triage",
+            gh_context.github_event_name
+        );
+        return Ok(ExitCode::SUCCESS);
+    };
+
+    // Instantiate the client and reconcile the labels and comments of the issue
+    let client = client::get_client()?;
+
+    let triage = Triage {
+        client: &client,
+        number,
+        rules: &configuration.triage.rules,
+        bot: &cli.bot,
+    };
+
+    let repos = vec![Repo {
+        org: org.to_string(),
+        repo: repo.to_string(),
+        ..Default::default()
+    }];
+    let code = reconcile::plan::run(&triage, &repos, &cli.plan).await?;
+
+    Ok(code)
+}
@@ -0,0 +1,373 @@
+use std::fmt;
+
+use action::github::{Comment, GitHub};
+use async_trait::async_trait;
+use config::types::{Repo, TriageRule, When};
+use reconcile::{error::Result, resource::Resource};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub enum Why {
+    // Label
+    AddLabel(String),
+    // Label
+    RemoveLabel(String),
+    // Prefix, Body
+    Comment(String, String),
+    // Prefix, Id, Body
+    UpdateComment(String, u64, String),
+    // Prefix, Id
+    DeleteComment(String, u64),
+}
+
+impl fmt::Display for Why {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Why::AddLabel(label) => write!(f, "add label {:?}", label),
+            Why::RemoveLabel(label) => write!(f, "remove label {:?}", label),
+            Why::Comment(prefix, _) => write!(f, "comment for {:?}", prefix),
+            Why::UpdateComment(prefix, id, _) => {
+                write!(f, "update comment for {:?} ({})", prefix, id)
+            }
+            Why::DeleteComment(prefix, id) => {
+                write!(f, "delete comment for {:?} ({})", prefix, id)
+            }
+        }
+    }
+}
+
+// Observed holds the labels and comments of the issue or pull request.
+#[derive(Serialize, Deserialize, PartialEq)]
+pub struct Observed {
+    labels: Vec<String>,
+    comments: Vec<Comment>,
+}
+
+// Triage keeps the placeholder labels and comments of `Configuration.triage`
+// on an issue or pull request, e.g. `needs-kind` until a `kind/*` label is
+// added. Running it again changes nothing.
+pub struct Triage<'a> {
+    pub client: &'a dyn GitHub,
+    pub number: u64,
+    pub rules: &'a [TriageRule],
+    // Login the comments are made with, those of anyone else are left alone
+    pub bot: &'a str,
+}
+
+// marker identifies the comment of a rule among the others of the issue
+fn marker(rule: &TriageRule) -> String {
+    format!("<!-- triage: {} -->", rule.prefix)
+}
+
+impl Triage<'_> {
+    // find_comment returns the bot comment of a rule, the ones posted before
+    // the marker was added are found by their text
+    fn find_comment<'c>(
+        &self,
+        comments: &'c [Comment],
+        rule: &TriageRule,
+        text: &str,
+    ) -> Option<&'c Comment> {
+        let marker = marker(rule);
+        comments
+            .iter()
+            .filter(|c| {
+                c.author
+                    .as_deref()
+                    .is_some_and(|author| author.eq_ignore_ascii_case(self.bot))
+            })
+            .find(|c| c.body.contains(&marker) || c.body.contains(text.trim()))
+    }
+}
+
+// applies tells whether the rule applies to an issue with these labels
+fn applies(rule: &TriageRule, labels: &[String]) -> bool {
+    let prefix = rule.prefix.to_lowercase();
+    let present = labels
+        .iter()
+        .any(|label| label.to_lowercase().starts_with(&prefix));
+
+    match rule.when {
+        When::Missing => !present,
+        When::Present => present,
+    }
+}
+
+#[async_trait]
+impl Resource for Triage<'_> {
+    const KIND: &'static str = "Triage";
+
+    type Desired = TriageRule;
+    type Observed = Observed;
+    type Change = Why;
+
+    fn desired(&self, _repo: &Repo) -> Vec<TriageRule> {
+        self.rules.to_vec()
+    }
+
+    async fn observe(&self, org: &str, repo: &str) -> Result<Observed> {
+        let labels = self
+            .client
+            .list_issue_labels(org, repo, self.number)
+            .await?;
+        let comments = self.client.list_comments(org, repo, self.number).await?;
+
+        Ok(Observed { labels, comments })
+    }
+
+    fn diff(&self, desired: &[TriageRule], observed: &Observed) -> Vec<Why> {
+        let mut updates: Vec<Why> = vec![];
+
+        for rule in desired {
+            let applies = applies(rule, &observed.labels);
+
+            if let Some(placeholder) = &rule.placeholder {
+                let labeled = observed
+                    .labels
+                    .iter()
+                    .any(|label| label.eq_ignore_ascii_case(placeholder));
+                if applies && !labeled {
+                    updates.push(Why::AddLabel(placeholder.clone()));
+                }
+                if !applies && labeled {
+                    updates.push(Why::RemoveLabel(placeholder.clone()));
+                }
+            }
+
+            if let Some(comment) = &rule.comment {
+                let body = format!("{}\n{}", marker(rule), comment);
+                let existing = self.find_comment(&observed.comments, rule, comment);
+
+                match (applies, existing) {
+                    (true, None) => updates.push(Why::Comment(rule.prefix.clone(), body)),
+                    (true, Some(existing)) if existing.body != body => {
+                        updates.push(Why::UpdateComment(rule.prefix.clone(), existing.id, body))
+                    }
+                    (false, Some(existing)) => {
+                        updates.push(Why::DeleteComment(rule.prefix.clone(), existing.id))
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        updates
+    }
+
+    async fn apply(&self, org: &str, repo: &str, change: &Why) -> Result<()> {
+        match change {
+            Why::AddLabel(label) => {
+                self.client
+                    .add_issue_labels(org, repo, self.number, std::slice::from_ref(label))
+                    .await?;
+                eprintln!("Label added: #{} {:?}", self.number, label);
+            }
+            Why::RemoveLabel(label) => {
+                self.client
+                    .remove_issue_label(org, repo, self.number, label)
+                    .await?;
+                eprintln!("Label removed: #{} {:?}", self.number, label);
+            }
+            Why::Comment(_, body) => {
+                let resp = self
+                    .client
+                    .create_comment(org, repo, self.number, body)
+                    .await?;
+                eprintln!("Comment created: {:?}", resp);
+            }
+            Why::UpdateComment(_, id, body) => {
+                let resp = self.client.update_comment(org, repo, *id, body).await?;
+                eprintln!("Comment updated: {:?}", resp);
+            }
+            Why::DeleteComment(_, id) => {
+                self.client.delete_comment(org, repo, *id).await?;
+                eprintln!("Comment deleted: #{} {}", self.number, id);
+            }
+        };
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use action::{
+        fake::{FakeGitHub, FakeRepo},
+        github::Comment,
+    };
+    use config::types::Triage as Rules;
+    use reconcile::testing::{apply_and_recompute, render, repos};
+
+    use crate::triage::Triage;
+
+    const BOT: &str = "github-actions[bot]";
+
+    const RULES: &str = r#"
+rules:
+  - prefix: triage/accepted
+    placeholder: needs-triage
+    comment: This issue is currently awaiting triage.
+  - prefix: kind/
+    placeholder: needs-kind
+  - prefix: priority/
+    placeholder: needs-priority
+  - prefix: good first issue
+    when: present
+    comment: This issue has been marked 'good first issue'
+"#;
+
+    fn client(labels: &[&str], comments: Vec<Comment>) -> FakeGitHub {
+        FakeGitHub::new().with_repo(
+            "org",
+            "repo",
+            FakeRepo {
+                issue_labels: HashMap::from([(1, labels.iter().map(|l| l.to_string()).collect())]),
+                comments: HashMap::from([(1, comments)]),
+                ..Default::default()
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn new_issue() {
+        let rules: Rules = serde_yml::from_str(RULES).unwrap();
+        let repos = repos(&["repo"]);
+        let client = client(&[], vec![]);
+        let triage = Triage {
+            client: &client,
+            number: 1,
+            rules: &rules.rules,
+            bot: BOT,
+        };
+
+        assert_eq!(
+            "\
+org/repo: add label \"needs-triage\"
+org/repo: comment for \"triage/accepted\"
+org/repo: add label \"needs-kind\"
+org/repo: add label \"needs-priority\"",
+            render(&triage, &repos).await
+        );
+
+        assert_eq!("", apply_and_recompute(&triage, &repos).await);
+
+        let repo = client.repo("org", "repo");
+        assert_eq!(
+            vec!["needs-triage", "needs-kind", "needs-priority"],
+            repo.issue_labels[&1]
+        );
+        assert_eq!(
+            "<!-- triage: triage/accepted -->\nThis issue is currently awaiting triage.",
+            repo.comments[&1][0].body
+        );
+    }
+
+    #[tokio::test]
+    async fn triaged_issue() {
+        let rules: Rules = serde_yml::from_str(RULES).unwrap();
+        let repos = repos(&["repo"]);
+        let client = client(
+            &[
+                "needs-triage",
+                "needs-kind",
+                "triage/accepted",
+                "Kind/Bug",
+                "good first issue",
+            ],
+            vec![
+                Comment {
+                    id: 1,
+                    body:
+                        "<!-- triage: triage/accepted -->\nThis issue is currently awaiting triage."
+                            .to_string(),
+                    author: Some(BOT.to_string()),
+                },
+                Comment {
+                    id: 2,
+                    body: "<!-- triage: good first issue -->\nOld text".to_string(),
+                    author: Some(BOT.to_string()),
+                },
+            ],
+        );
+        let triage = Triage {
+            client: &client,
+            number: 1,
+            rules: &rules.rules,
+            bot: BOT,
+        };
+
+        assert_eq!(
+            "\
+org/repo: remove label \"needs-triage\"
+org/repo: delete comment for \"triage/accepted\" (1)
+org/repo: remove label \"needs-kind\"
+org/repo: add label \"needs-priority\"
+org/repo: update comment for \"good first issue\" (2)",
+            render(&triage, &repos).await
+        );
+
+        assert_eq!("", apply_and_recompute(&triage, &repos).await);
+
+        let repo = client.repo("org", "repo");
+        assert_eq!(
+            vec![
+                "triage/accepted",
+                "Kind/Bug",
+                "good first issue",
+                "needs-priority"
+            ],
+            repo.issue_labels[&1]
+        );
+        assert_eq!(1, repo.comments[&1].len());
+    }
+
+    #[tokio::test]
+    async fn legacy_comments() {
+        let rules: Rules = serde_yml::from_str(RULES).unwrap();
+        let repos = repos(&["repo"]);
+        let client = client(
+            &["needs-triage", "needs-kind", "needs-priority"],
+            vec![
+                Comment {
+                    id: 1,
+                    body: "<!-- triage: good first issue -->\nThis issue has been marked 'good first issue'"
+                        .to_string(),
+                    author: Some("someone".to_string()),
+                },
+                Comment {
+                    id: 2,
+                    body: "This issue is currently awaiting triage.\n".to_string(),
+                    author: Some(BOT.to_string()),
+                },
+                Comment {
+                    id: 3,
+                    body: "This issue has been marked 'good first issue'\n".to_string(),
+                    author: Some(BOT.to_string()),
+                },
+            ],
+        );
+        let triage = Triage {
+            client: &client,
+            number: 1,
+            rules: &rules.rules,
+            bot: BOT,
+        };
+
+        assert_eq!(
+            "\
+org/repo: update comment for \"triage/accepted\" (2)
+org/repo: delete comment for \"good first issue\" (3)",
+            render(&triage, &repos).await
+        );
+
+        assert_eq!("", apply_and_recompute(&triage, &repos).await);
+
+        let repo = client.repo("org", "repo");
+        assert_eq!(
+            vec![1, 2],
+            repo.comments[&1].iter().map(|c| c.id).collect::<Vec<_>>()
+        );
+    }
+}
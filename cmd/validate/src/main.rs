@@ -0,0 +1,69 @@
+use std::{
+    fs,
+    process::{ExitCode, Termination},
+};
+
+use action::commands::{ActionCommand, Level, Properties};
+use clap::Parser;
+use config::validate::validate;
+
+mod error;
+
+#[derive(clap::Parser, Debug)]
+#[command(
+    author,
+    version = env ! ("CARGO_PKG_VERSION"),
+    long_about = None
+)]
+struct Cli {
+    /// Path to config.yaml, can be repeated
+    #[arg(long, required = true)]
+    config: Vec<String>,
+}
+
+fn main() -> impl Termination {
+    match exec() {
+        Ok(code) => code,
+        Err(error) => {
+            println!("{:#?}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// exec reports every problem of every file, not only the first one.
+fn exec() -> crate::error::Result<ExitCode> {
+    let cli = Cli::parse();
+
+    let mut invalid = false;
+    for path in &cli.config {
+        let source = fs::read_to_string(path)?;
+
+        let problems = validate(&source);
+        for problem in &problems {
+            let message = match problem.path.is_empty() {
+                true => problem.message.clone(),
+                false => format!("{}: {}", problem.path, problem.message),
+            };
+            ActionCommand::Annotation(
+                Level::Error,
+                Properties::file(path).at(problem.line, problem.column),
+                message,
+            )
+            .send_command();
+        }
+
+        match problems.len() {
+            0 => ActionCommand::Notice(format!("{} is valid", path)).send_command(),
+            count => {
+                invalid = true;
+                println!("{} has {} problem(s)", path, count);
+            }
+        }
+    }
+
+    match invalid {
+        true => Ok(ExitCode::FAILURE),
+        false => Ok(ExitCode::SUCCESS),
+    }
+}
@@ -6,11 +6,14 @@ pub enum Error {
     Action(#[from] action::error::Error),
 
     #[error(transparent)]
-    PR(#[from] pr::error::Error),
+    Config(#[from] config::error::Error),
 
     #[error(transparent)]
     IO(#[from] std::io::Error),
 
+    #[error("The PR breaks {} rule(s):\n{}", .0.len(), .0.join("\n"))]
+    Rules(Vec<String>),
+
     #[error("Unable to unmarshal PullRequest {file_path:?}")]
     UnmarshalPullRequest {
         file_path: String,
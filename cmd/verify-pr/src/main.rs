@@ -1,22 +1,31 @@
-use action::context::GitHubVariables;
-use pr::prefix::PRType;
+use action::{client, commands::ActionCommand, context::GitHubVariables};
+use config::types::{Configuration, Verify};
+use pr::{convention::Convention, prefix::Backport};
+use report::Report;
+use rules::PullRequest;
 use serde::Deserialize;
 use std::fs;
 
 mod error;
+mod report;
+mod rules;
 
-#[derive(Debug, Deserialize)]
-struct Event {
-    pub pull_request: PullRequestEvent,
-}
+// Path to the configuration, resolved from the crate as the action runs from
+// its own directory
+const CONFIG_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../pkg/config/config.yaml");
+
+// Login of the GITHUB_TOKEN the comment is made with, BOT_LOGIN overrides it
+// when the action is given another token
+const BOT_LOGIN: &str = "github-actions[bot]";
 
 #[derive(Debug, Deserialize)]
-struct PullRequestEvent {
-    title: String,
+struct Event {
+    pub pull_request: PullRequest,
 }
 
-fn main() -> error::Result<()> {
-    let result = verify_pr();
+#[tokio::main]
+async fn main() -> error::Result<()> {
+    let result = verify_pr().await;
     match &result {
         Ok(_) => {}
         Err(error) => println!("{error}"),
@@ -24,11 +33,11 @@ fn main() -> error::Result<()> {
     result
 }
 
-fn verify_pr() -> error::Result<()> {
+async fn verify_pr() -> error::Result<()> {
     let gh_context = GitHubVariables::from_env()?;
 
     // Parse the event
-    let event_file = fs::read_to_string(gh_context.github_event_path)?;
+    let event_file = fs::read_to_string(&gh_context.github_event_path)?;
 
     let event: Event = serde_json::from_str(&event_file).map_err(|err| {
         crate::error::Error::UnmarshalPullRequest {
@@ -37,25 +46,142 @@ fn verify_pr() -> error::Result<()> {
         }
     })?;
 
+    let configuration = Configuration::from_path(CONFIG_PATH)?;
+
+    let convention = title_convention(&gh_context, &configuration);
+    let report = verify(
+        &event.pull_request,
+        convention.as_ref(),
+        &configuration.verify,
+    );
+    for problem in &report.problems {
+        ActionCommand::Error(problem.clone()).send_command();
+    }
+
+    // Report on the PR itself when a token is available, e.g. not locally
+    match (client::get_client(), gh_context.repository()) {
+        (Ok(client), Some((org, repo))) => {
+            let bot = std::env::var("BOT_LOGIN").unwrap_or(BOT_LOGIN.to_string());
+            report::publish(&client, org, repo, &bot, &event.pull_request, &report).await?;
+        }
+        (Err(action::error::Error::Env(_)), _) | (_, None) => {
+            ActionCommand::Warning(
+                "No GITHUB_TOKEN, the results are not posted on the PR".to_string(),
+            )
+            .send_command();
+        }
+        (Err(error), _) => return Err(error.into()),
+    }
+
+    if !report.is_ok() {
+        return Err(crate::error::Error::Rules(report.problems));
+    }
+
+    Ok(())
+}
+
+// verify checks the title of the PR and, once it is valid, the rules of
+// config.yaml.
+fn verify(pull_request: &PullRequest, convention: &dyn Convention, rules: &Verify) -> Report {
     // Check the title of the PR
-    let pr_type = PRType::from_title(&event.pull_request.title)?;
+    let backport = Backport::from_title(&pull_request.title);
+    let pr_type = match &backport {
+        // Backports carry the title of the original PR after the tag
+        Some(backport) => convention.parse(&backport.title),
+        None => convention.parse(&pull_request.title),
+    };
+
+    let pr_type = match pr_type {
+        Ok(pr_type) => pr_type,
+        Err(error) => {
+            return Report {
+                problems: vec![error.to_string()],
+                suggested_title: report::suggest_title(convention, rules, pull_request),
+            }
+        }
+    };
 
     println!("{:?}", pr_type);
-    Ok(())
+
+    // Drafts are still being worked on, only their title is checked
+    if pull_request.draft {
+        ActionCommand::Notice("Draft PR, only the title is checked".to_string()).send_command();
+        return Report::default();
+    }
+
+    Report {
+        problems: rules::check(rules, pull_request, &pr_type, backport.as_ref()),
+        suggested_title: None,
+    }
+}
+
+// title_convention looks up the convention of the repository in config.yaml,
+// repositories we don't manage use the emoji prefixes.
+fn title_convention(
+    gh_context: &GitHubVariables,
+    configuration: &Configuration,
+) -> Box<dyn Convention> {
+    let title_convention = gh_context
+        .repository()
+        .and_then(|(org, repo)| configuration.find_repo(org, repo))
+        .map(|repo| repo.convention.clone())
+        .unwrap_or_default();
+
+    title_convention.convention()
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::main;
+    use config::types::Configuration;
+    use pr::convention::Emoji;
     use tempfile::NamedTempFile;
 
+    use crate::{
+        main,
+        rules::{Label, PullRequest},
+        verify, CONFIG_PATH,
+    };
+
+    #[test]
+    fn config_rules() {
+        let configuration = Configuration::from_path(CONFIG_PATH).unwrap();
+        let pull_request = |title: &str, body: &str, labels: &[&str]| PullRequest {
+            title: title.to_string(),
+            body: Some(body.to_string()),
+            labels: labels
+                .iter()
+                .map(|name| Label {
+                    name: name.to_string(),
+                })
+                .collect(),
+            ..Default::default()
+        };
+
+        let report = verify(
+            &pull_request(":bug: Fix bug", "Fixes #1", &["kind/bug"]),
+            &Emoji,
+            &configuration.verify,
+        );
+        assert!(report.is_ok());
+
+        let report = verify(
+            &pull_request(":bug: Fix bug", "", &["kind/feature"]),
+            &Emoji,
+            &configuration.verify,
+        );
+        assert_eq!(2, report.problems.len(), "{:?}", report.problems);
+    }
+
     #[test]
     fn read_from_file() {
         std::env::set_var("CI", "true");
         std::env::set_var("GITHUB_ACTIONS", "true");
         std::env::set_var("GITHUB_EVENT_NAME", "foo");
+        std::env::set_var("GITHUB_REPOSITORY", "org/repo");
+        std::env::remove_var("GITHUB_TOKEN");
 
-        let event_that_generates_ok = "{\"pull_request\":{\"title\":\"WIP: :bug: Fix bug\"}}";
+        let event_that_generates_ok =
+            "{\"pull_request\":{\"title\":\"WIP: :bug: Fix bug\",\"body\":\"Fixes #1\"}}";
         let event_that_generates_error =
             "{\"pull_request\":{\"title\":\"WIP: [docs] Update documentation\"}}";
 
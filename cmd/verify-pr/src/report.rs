@@ -0,0 +1,324 @@
+use action::github::{Annotation, CheckRun, CheckRunOutput, GitHub};
+use config::types::Verify;
+use pr::convention::{Convention, ConventionalCommits, Emoji};
+
+use crate::rules::PullRequest;
+
+// Name of the check run, also shown on the PR
+const CHECK_NAME: &str = "verify-pr";
+
+// MARKER identifies the comment of verify-pr among the others of the PR
+const MARKER: &str = "<!-- verify-pr -->";
+
+// Report is the outcome of verifying a PR.
+#[derive(Debug, Default, PartialEq)]
+pub struct Report {
+    pub problems: Vec<String>,
+    pub suggested_title: Option<String>,
+}
+
+impl Report {
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    // summary explains the problems in markdown
+    pub fn summary(&self) -> String {
+        if self.is_ok() {
+            return "The title and description of the PR follow the rules.".to_string();
+        }
+
+        let mut summary = String::from("### :x: The PR does not follow the rules\n\n```text\n");
+        summary.push_str(&self.problems.join("\n\n"));
+        summary.push_str("\n```\n");
+        if let Some(title) = &self.suggested_title {
+            summary.push_str(&format!("\n**Suggested title:** `{}`\n", title));
+        }
+        summary
+    }
+
+    pub fn check_run(&self, head_sha: &str) -> CheckRun {
+        let (conclusion, title) = match self.is_ok() {
+            true => ("success", "PR is valid".to_string()),
+            false => (
+                "failure",
+                format!("{} problem(s) found", self.problems.len()),
+            ),
+        };
+
+        // Annotations need a file of the commit, the title and description of
+        // the PR have none so they are reported on the .github directory
+        let annotations = self
+            .problems
+            .iter()
+            .map(|problem| Annotation {
+                path: ".github".to_string(),
+                start_line: 1,
+                end_line: 1,
+                annotation_level: "failure".to_string(),
+                title: problem.lines().next().map(|line| line.to_string()),
+                message: problem.clone(),
+            })
+            .collect();
+
+        CheckRun {
+            name: CHECK_NAME.to_string(),
+            head_sha: head_sha.to_string(),
+            conclusion: conclusion.to_string(),
+            output: CheckRunOutput {
+                title,
+                summary: self.summary(),
+                annotations,
+            },
+        }
+    }
+}
+
+// suggest_title proposes a valid title for an invalid one. The type comes from
+// a raw emoji or the other convention in the title, or else from the kind
+// label of the PR.
+pub fn suggest_title(
+    convention: &dyn Convention,
+    rules: &Verify,
+    pull_request: &PullRequest,
+) -> Option<String> {
+    let title = pull_request.title.trim();
+
+    let pr_type = Emoji
+        .classify(title)
+        .or_else(|| ConventionalCommits.classify(title))
+        .or_else(|| {
+            rules
+                .kind_labels
+                .iter()
+                .find(|(_, label)| {
+                    pull_request
+                        .labels
+                        .iter()
+                        .any(|l| l.name.eq_ignore_ascii_case(label))
+                })
+                .map(|(kind, _)| kind.pr_type(title.to_string()))
+        })?;
+
+    if pr_type.title().is_empty() {
+        return None;
+    }
+
+    let suggested = convention.format(&pr_type);
+    match convention.parse(&suggested).is_ok() && suggested != title {
+        true => Some(suggested),
+        false => None,
+    }
+}
+
+// publish reports on the PR, a check run on its head commit and a single
+// comment that is kept up to date and deleted once the PR is valid. Only the
+// comments of the bot are considered, anyone can write the marker.
+pub async fn publish(
+    client: &dyn GitHub,
+    org: &str,
+    repo: &str,
+    bot: &str,
+    pull_request: &PullRequest,
+    report: &Report,
+) -> action::error::Result<()> {
+    client
+        .create_check_run(org, repo, &report.check_run(&pull_request.head.sha))
+        .await?;
+
+    let existing = client
+        .list_comments(org, repo, pull_request.number)
+        .await?
+        .into_iter()
+        .filter(|comment| {
+            comment
+                .author
+                .as_deref()
+                .is_some_and(|author| author.eq_ignore_ascii_case(bot))
+        })
+        .find(|comment| comment.body.starts_with(MARKER));
+
+    let body = format!("{}\n{}", MARKER, report.summary());
+    match (existing, report.is_ok()) {
+        (None, true) => {}
+        (None, false) => {
+            client
+                .create_comment(org, repo, pull_request.number, &body)
+                .await?;
+        }
+        (Some(comment), false) => {
+            if comment.body != body {
+                client.update_comment(org, repo, comment.id, &body).await?;
+            }
+        }
+        (Some(comment), true) => {
+            client.delete_comment(org, repo, comment.id).await?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use action::{
+        fake::{FakeGitHub, FakeRepo},
+        github::Comment,
+    };
+    use config::types::{PRKind, Verify};
+    use pr::convention::{ConventionalCommits, Emoji};
+
+    use crate::{
+        report::{publish, suggest_title, Report},
+        rules::{Head, Label, PullRequest},
+    };
+
+    fn pull_request(title: &str, labels: &[&str]) -> PullRequest {
+        PullRequest {
+            number: 7,
+            title: title.to_string(),
+            labels: labels
+                .iter()
+                .map(|name| Label {
+                    name: name.to_string(),
+                })
+                .collect(),
+            head: Head {
+                sha: "abc".to_string(),
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn suggest() {
+        let rules = Verify {
+            kind_labels: HashMap::from([(PRKind::Bug, "kind/bug".to_string())]),
+            ..Default::default()
+        };
+
+        let suggest = |title: &str, labels: &[&str]| {
+            suggest_title(&Emoji, &rules, &pull_request(title, labels))
+        };
+        assert_eq!(
+            Some(":bug: Fix bug".to_string()),
+            suggest("🐛 Fix bug", &[])
+        );
+        assert_eq!(
+            Some(":sparkles: Add feature".to_string()),
+            suggest("feat(ui): Add feature", &[])
+        );
+        assert_eq!(
+            Some(":bug: Fix bug".to_string()),
+            suggest("Fix bug", &["kind/bug"])
+        );
+        assert_eq!(None, suggest("Fix bug", &[]));
+
+        assert_eq!(
+            Some("fix: Fix bug".to_string()),
+            suggest_title(
+                &ConventionalCommits,
+                &rules,
+                &pull_request(":bug: Fix bug", &[])
+            )
+        );
+    }
+
+    const BOT: &str = "github-actions[bot]";
+
+    #[tokio::test]
+    async fn sticky_comment() {
+        let client = FakeGitHub::new().with_repo("org", "repo", FakeRepo::default());
+        let pull_request = pull_request("Fix bug", &[]);
+        let comments = || {
+            client
+                .repo("org", "repo")
+                .comments
+                .remove(&7)
+                .unwrap_or_default()
+        };
+
+        let report = Report {
+            problems: vec!["Invalid prefix".to_string()],
+            suggested_title: Some(":bug: Fix bug".to_string()),
+        };
+        publish(&client, "org", "repo", BOT, &pull_request, &report)
+            .await
+            .unwrap();
+        assert_eq!(1, comments().len());
+        assert!(comments()[0].body.contains("`:bug: Fix bug`"));
+
+        let report = Report {
+            problems: vec!["Another problem".to_string()],
+            suggested_title: None,
+        };
+        publish(&client, "org", "repo", BOT, &pull_request, &report)
+            .await
+            .unwrap();
+        assert_eq!(1, comments().len());
+        assert!(comments()[0].body.contains("Another problem"));
+
+        publish(
+            &client,
+            "org",
+            "repo",
+            BOT,
+            &pull_request,
+            &Report::default(),
+        )
+        .await
+        .unwrap();
+        assert!(comments().is_empty());
+
+        let check_runs = client.repo("org", "repo").check_runs;
+        assert_eq!(
+            vec!["failure", "failure", "success"],
+            check_runs
+                .iter()
+                .map(|c| c.conclusion.as_str())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!("abc", check_runs[0].head_sha);
+        assert_eq!(1, check_runs[0].output.annotations.len());
+    }
+
+    #[tokio::test]
+    async fn foreign_comment() {
+        let quoted = Comment {
+            id: 1,
+            body: "<!-- verify-pr -->\nquoting the bot".to_string(),
+            author: Some("someone".to_string()),
+        };
+        let client = FakeGitHub::new().with_repo(
+            "org",
+            "repo",
+            FakeRepo {
+                comments: HashMap::from([(7, vec![quoted.clone()])]),
+                ..Default::default()
+            },
+        );
+        let pull_request = pull_request("Fix bug", &[]);
+
+        let report = Report {
+            problems: vec!["Invalid prefix".to_string()],
+            suggested_title: None,
+        };
+        publish(&client, "org", "repo", BOT, &pull_request, &report)
+            .await
+            .unwrap();
+        publish(
+            &client,
+            "org",
+            "repo",
+            BOT,
+            &pull_request,
+            &Report::default(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(vec![quoted], client.repo("org", "repo").comments[&7]);
+    }
+}
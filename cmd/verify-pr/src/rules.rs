@@ -0,0 +1,335 @@
+use config::types::{PRKind, Verify};
+use pr::prefix::{Backport, PRType};
+use regex::Regex;
+use serde::Deserialize;
+use version::branch::Branch;
+
+// PullRequest is the part of the `pull_request` event the rules look at.
+#[derive(Debug, Default, Deserialize)]
+pub struct PullRequest {
+    #[serde(default)]
+    pub number: u64,
+
+    pub title: String,
+
+    #[serde(default)]
+    pub body: Option<String>,
+
+    #[serde(default)]
+    pub labels: Vec<Label>,
+
+    #[serde(default)]
+    pub base: Base,
+
+    #[serde(default)]
+    pub head: Head,
+
+    #[serde(default)]
+    pub draft: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct Label {
+    pub name: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct Base {
+    // Name of the branch the PR merges into, e.g. `main`
+    #[serde(rename = "ref")]
+    pub name: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct Head {
+    // Sha of the last commit of the PR
+    pub sha: String,
+}
+
+// check returns a message for every rule the PR breaks, the title is expected
+// to be valid already. The type of a backport is the one of the original PR,
+// which went through the other rules when it was merged.
+pub fn check(
+    rules: &Verify,
+    pull_request: &PullRequest,
+    pr_type: &PRType,
+    backport: Option<&Backport>,
+) -> Vec<String> {
+    let mut violations: Vec<String> = vec![];
+    let kind = PRKind::from(pr_type);
+    let body = pull_request.body.as_deref().unwrap_or_default();
+
+    if rules.release_branches {
+        violations.extend(check_release_branch(pull_request, kind, backport));
+    }
+    if backport.is_some() {
+        return violations;
+    }
+
+    if let Some(wanted) = rules.kind_labels.get(&kind) {
+        let kind_labels: Vec<&str> = pull_request
+            .labels
+            .iter()
+            .map(|l| l.name.as_str())
+            .filter(|name| name.starts_with("kind/"))
+            .collect();
+
+        if !kind_labels.is_empty() && !kind_labels.iter().any(|l| l.eq_ignore_ascii_case(wanted)) {
+            violations.push(format!(
+                "the title says {} but the PR is labeled {}, expected {}",
+                kind,
+                kind_labels.join(", "),
+                wanted
+            ));
+        }
+    }
+
+    if rules.linked_issue.contains(&kind) && !links_issue(body) {
+        violations.push(format!(
+            "{} PRs must link the issue they address, e.g. \"Fixes #123\"",
+            kind
+        ));
+    }
+
+    if rules.release_note.contains(&kind) && release_note(body).is_none() {
+        violations.push(format!(
+            "{} PRs need a release note, add a ```release-note block to the description",
+            kind
+        ));
+    }
+
+    violations
+}
+
+// check_release_branch enforces VERSIONING.md, `release-X.Y` branches only
+// receive fixes and backports are tagged with the branch they target.
+fn check_release_branch(
+    pull_request: &PullRequest,
+    kind: PRKind,
+    backport: Option<&Backport>,
+) -> Vec<String> {
+    let mut violations: Vec<String> = vec![];
+    let base = &pull_request.base.name;
+    let body = pull_request.body.as_deref().unwrap_or_default();
+
+    if let Some(backport) = backport {
+        if backport.branch != *base {
+            violations.push(format!(
+                "the title is tagged [{}] but the PR targets {}",
+                backport.branch, base
+            ));
+        }
+        if !references_pull_request(body) {
+            violations.push(
+                "backports must reference the original PR, e.g. \"Backport of #123\"".to_string(),
+            );
+        }
+    }
+
+    let release_branch = matches!(base.parse(), Ok(Branch::Release { .. }));
+    if release_branch && matches!(kind, PRKind::Feature | PRKind::Breaking) {
+        violations.push(format!(
+            "{} only receives fixes, {} changes go to main, see VERSIONING.md",
+            base, kind
+        ));
+    }
+
+    violations
+}
+
+// references_pull_request looks for a PR number or link, e.g. `#123`
+fn references_pull_request(body: &str) -> bool {
+    let pull_regex =
+        Regex::new(r"(^|[^\w&])#\d+\b|https://github\.com/[\w.-]+/[\w.-]+/pull/\d+").unwrap();
+    pull_regex.is_match(body)
+}
+
+// links_issue looks for a closing keyword followed by an issue, e.g.
+// `Fixes #123`, `closes org/repo#4` or a link to the issue.
+fn links_issue(body: &str) -> bool {
+    let issue_regex = Regex::new(
+        r"(?i)\b(close[sd]?|fix(e[sd])?|resolve[sd]?):?\s+([\w.-]+/[\w.-]+#\d+|#\d+|https://github\.com/[\w.-]+/[\w.-]+/issues/\d+)",
+    )
+    .unwrap();
+    issue_regex.is_match(body)
+}
+
+// release_note returns the content of the ```release-note block, None when
+// it is missing, empty or NONE.
+fn release_note(body: &str) -> Option<String> {
+    let note_regex = Regex::new(r"(?s)```release-note[^\S\n]*\r?\n(.*?)```").unwrap();
+
+    let note = note_regex.captures(body)?[1].trim().to_string();
+    match note.is_empty() || note.eq_ignore_ascii_case("none") {
+        true => None,
+        false => Some(note),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use config::types::{PRKind, Verify};
+    use pr::prefix::{Backport, PRType};
+
+    use crate::rules::{
+        check, links_issue, references_pull_request, release_note, Base, Label, PullRequest,
+    };
+
+    fn rules() -> Verify {
+        Verify {
+            kind_labels: HashMap::from([
+                (PRKind::Bug, "kind/bug".to_string()),
+                (PRKind::Feature, "kind/feature".to_string()),
+            ]),
+            linked_issue: vec![PRKind::Bug],
+            release_note: vec![PRKind::Breaking],
+            release_branches: true,
+        }
+    }
+
+    fn pull_request(title: &str, body: &str, labels: &[&str], base: &str) -> PullRequest {
+        PullRequest {
+            title: title.to_string(),
+            body: Some(body.to_string()),
+            labels: labels
+                .iter()
+                .map(|name| Label {
+                    name: name.to_string(),
+                })
+                .collect(),
+            base: Base {
+                name: base.to_string(),
+            },
+            ..Default::default()
+        }
+    }
+
+    fn violations(pull_request: &PullRequest) -> Vec<String> {
+        let backport = Backport::from_title(&pull_request.title);
+        let title = match &backport {
+            Some(backport) => &backport.title,
+            None => &pull_request.title,
+        };
+        let pr_type = PRType::from_title(title).unwrap();
+        check(&rules(), pull_request, &pr_type, backport.as_ref())
+    }
+
+    #[test]
+    fn valid() {
+        assert!(violations(&pull_request(
+            ":bug: Fix bug",
+            "Fixes #12",
+            &["kind/bug", "lgtm"],
+            "main"
+        ))
+        .is_empty());
+        assert!(violations(&pull_request(
+            ":warning: Drop the v1 API",
+            "```release-note\nThe v1 API is gone, use v2.\n```",
+            &[],
+            "main"
+        ))
+        .is_empty());
+        assert!(violations(&pull_request(":sparkles: Add feature", "", &[], "main")).is_empty());
+        assert!(violations(&pull_request(":seedling: Bump", "", &["kind/bug"], "main")).is_empty());
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(
+            vec![
+                "the title says bug but the PR is labeled kind/feature, expected kind/bug",
+                "bug PRs must link the issue they address, e.g. \"Fixes #123\"",
+            ],
+            violations(&pull_request(
+                ":bug: Fix bug",
+                "See #12",
+                &["kind/feature"],
+                "main"
+            ))
+        );
+        assert_eq!(
+            vec![
+                "breaking PRs need a release note, add a ```release-note block to the description"
+            ],
+            violations(&pull_request(
+                ":warning: Drop the v1 API",
+                "```release-note\nNONE\n```",
+                &[],
+                "main"
+            ))
+        );
+        assert_eq!(
+            vec!["release-0.2 only receives fixes, feature changes go to main, see VERSIONING.md"],
+            violations(&pull_request(
+                ":sparkles: Add feature",
+                "",
+                &[],
+                "release-0.2"
+            ))
+        );
+    }
+
+    #[test]
+    fn release_branch() {
+        assert!(violations(&pull_request(
+            ":seedling: [release-0.2] :bug: Fix bug",
+            "# Description\nBackport of #12 to `release-0.2`.",
+            &[],
+            "release-0.2"
+        ))
+        .is_empty());
+        assert!(violations(&pull_request(
+            ":bug: Fix bug only on this branch",
+            "Fixes #13",
+            &[],
+            "release-0.2"
+        ))
+        .is_empty());
+
+        assert_eq!(
+            vec![
+                "the title is tagged [release-0.2] but the PR targets release-0.3",
+                "backports must reference the original PR, e.g. \"Backport of #123\"",
+                "release-0.3 only receives fixes, breaking changes go to main, see VERSIONING.md",
+            ],
+            violations(&pull_request(
+                "[release-0.2] :warning: Drop the v1 API",
+                "",
+                &[],
+                "release-0.3"
+            ))
+        );
+    }
+
+    #[test]
+    fn pull_request_reference() {
+        assert!(references_pull_request("Backport of #12"));
+        assert!(references_pull_request(
+            "See https://github.com/org/repo/pull/12"
+        ));
+        assert!(!references_pull_request("Backport of the fix"));
+        assert!(!references_pull_request("&#39;quoted&#39;"));
+    }
+
+    #[test]
+    fn linked_issue() {
+        assert!(links_issue("This PR fixes #123."));
+        assert!(links_issue("Closes: org/repo#4"));
+        assert!(links_issue("resolves https://github.com/org/repo/issues/5"));
+        assert!(!links_issue("Related to #123"));
+        assert!(!links_issue("prefixes #123"));
+    }
+
+    #[test]
+    fn release_notes() {
+        assert_eq!(
+            Some("Something changed".to_string()),
+            release_note("Text\n```release-note\r\nSomething changed\r\n```\n")
+        );
+        assert_eq!(None, release_note("```release-note\n\n```"));
+        assert_eq!(None, release_note("No notes"));
+    }
+}
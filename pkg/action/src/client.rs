@@ -1,11 +1,62 @@
-use octocrab::Octocrab;
+use std::collections::{HashMap, HashSet};
+
+use async_trait::async_trait;
+use octocrab::{models::CommentId, Octocrab, Page};
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
 use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::github::{
+    CheckRun, Comment, GitHub, Issue, IssuePayload, Label, Milestone, MilestonePayload,
+    PullRequest, PullRequestDetails, PullRequestPayload, Reference, Release,
+};
 
 #[derive(Deserialize, Debug)]
 struct EnvVars {
     pub github_token: String,
 }
 
+// GitRef is the response of the git references API.
+#[derive(Deserialize)]
+struct GitRef {
+    #[serde(rename = "ref")]
+    name: String,
+    object: GitObject,
+}
+
+#[derive(Deserialize)]
+struct GitObject {
+    sha: String,
+}
+
+// GeneratedNotes is the response of the generate release notes API.
+#[derive(Deserialize)]
+struct GeneratedNotes {
+    body: String,
+}
+
+// label_route is the route of a label, its name is encoded as it may hold
+// spaces or slashes, e.g. `good first issue` or `kind/bug`.
+fn label_route(org: &str, repo: &str, name: &str) -> String {
+    format!(
+        "/repos/{}/{}/labels/{}",
+        org,
+        repo,
+        utf8_percent_encode(name, NON_ALPHANUMERIC)
+    )
+}
+
+// is_not_found reports a missing resource, GitHub answers 422 instead of 404
+// for a commit that does not exist.
+fn is_not_found(error: &octocrab::Error) -> bool {
+    match error {
+        octocrab::Error::GitHub { source, .. } => {
+            matches!(source.status_code.as_u16(), 404 | 422)
+        }
+        _ => false,
+    }
+}
+
 pub fn get_client() -> crate::error::Result<Octocrab> {
     let github_token = envy::from_env::<EnvVars>()?.github_token;
 
@@ -13,3 +64,1030 @@ pub fn get_client() -> crate::error::Result<Octocrab> {
 
     Ok(octocrab)
 }
+
+impl From<octocrab::models::Label> for Label {
+    fn from(label: octocrab::models::Label) -> Self {
+        Label {
+            name: label.name,
+            color: label.color,
+            description: label.description,
+        }
+    }
+}
+
+impl From<octocrab::models::Milestone> for Milestone {
+    fn from(milestone: octocrab::models::Milestone) -> Self {
+        Milestone {
+            number: milestone.number,
+            title: milestone.title,
+            description: milestone.description,
+            state: milestone.state,
+            due_on: milestone.due_on,
+            open_issues: milestone.open_issues,
+        }
+    }
+}
+
+impl From<octocrab::models::issues::Issue> for Issue {
+    fn from(issue: octocrab::models::issues::Issue) -> Self {
+        let state = match issue.state {
+            octocrab::models::IssueState::Open => "open",
+            _ => "closed",
+        };
+
+        Issue {
+            number: issue.number,
+            title: issue.title,
+            state: state.to_string(),
+            milestone: issue.milestone.map(|milestone| milestone.number),
+            pull_request: issue.pull_request.is_some(),
+        }
+    }
+}
+
+impl From<octocrab::models::issues::Comment> for Comment {
+    fn from(comment: octocrab::models::issues::Comment) -> Self {
+        Comment {
+            id: comment.id.into_inner(),
+            body: comment.body.unwrap_or_default(),
+            author: Some(comment.user.login),
+        }
+    }
+}
+
+#[async_trait]
+impl GitHub for Octocrab {
+    async fn list_labels(&self, org: &str, repo: &str) -> crate::error::Result<Vec<Label>> {
+        let per_page = 100;
+
+        let mut current_labels: Vec<Label> = vec![];
+
+        let mut page: u32 = 1;
+        loop {
+            let resp = self
+                .issues(org, repo)
+                .list_labels_for_repo()
+                .page(page)
+                .per_page(per_page)
+                .send()
+                .await?;
+
+            let items = resp.items;
+            current_labels.extend(items.into_iter().map(Label::from));
+
+            match resp.next {
+                Some(_url) => page += 1,
+                None => break,
+            }
+        }
+
+        Ok(current_labels)
+    }
+
+    async fn create_label(
+        &self,
+        org: &str,
+        repo: &str,
+        label: &Label,
+    ) -> crate::error::Result<Label> {
+        let resp = self
+            .issues(org, repo)
+            .create_label(
+                &label.name,
+                &label.color,
+                label.description.clone().unwrap_or("".to_string()),
+            )
+            .await?;
+        Ok(resp.into())
+    }
+
+    async fn update_label(
+        &self,
+        org: &str,
+        repo: &str,
+        name: &str,
+        label: &Label,
+    ) -> crate::error::Result<Label> {
+        let resp: octocrab::models::Label = self
+            .patch(
+                &label_route(org, repo, name),
+                Some(&json!({
+                    "name": label.name,
+                    "color": label.color,
+                    "description": label.description,
+                })),
+            )
+            .await?;
+        Ok(resp.into())
+    }
+
+    async fn delete_label(&self, org: &str, repo: &str, name: &str) -> crate::error::Result<()> {
+        let resp = self
+            ._delete(label_route(org, repo, name), None::<&()>)
+            .await?;
+        octocrab::map_github_error(resp).await?;
+        Ok(())
+    }
+
+    async fn list_milestones(&self, org: &str, repo: &str) -> crate::error::Result<Vec<Milestone>> {
+        list_milestones(self, org, repo, "open").await
+    }
+
+    async fn list_closed_milestones(
+        &self,
+        org: &str,
+        repo: &str,
+    ) -> crate::error::Result<Vec<Milestone>> {
+        list_milestones(self, org, repo, "closed").await
+    }
+
+    async fn create_milestone(
+        &self,
+        org: &str,
+        repo: &str,
+        milestone: &MilestonePayload,
+    ) -> crate::error::Result<Milestone> {
+        let resp: octocrab::models::Milestone = self
+            .post(
+                format!("/repos/{}/{}/milestones", org, repo),
+                Some(milestone),
+            )
+            .await?;
+        Ok(resp.into())
+    }
+
+    async fn update_milestone(
+        &self,
+        org: &str,
+        repo: &str,
+        number: i64,
+        milestone: &MilestonePayload,
+    ) -> crate::error::Result<Milestone> {
+        let resp: octocrab::models::Milestone = self
+            .patch(
+                &format!("/repos/{}/{}/milestones/{}", org, repo, number),
+                Some(milestone),
+            )
+            .await?;
+        Ok(resp.into())
+    }
+
+    async fn list_milestone_issues(
+        &self,
+        org: &str,
+        repo: &str,
+        milestone: i64,
+    ) -> crate::error::Result<Vec<Issue>> {
+        let mut issues: Vec<Issue> = vec![];
+
+        let mut page: u32 = 1;
+        loop {
+            let resp: Page<octocrab::models::issues::Issue> = self
+                .issues(org, repo)
+                .list()
+                .page(page)
+                .state(octocrab::params::State::Open) // Get only open issues
+                .milestone(milestone as u64)
+                .send()
+                .await?;
+
+            let items = resp.items;
+            issues.extend(items.into_iter().map(Issue::from));
+
+            match resp.next {
+                Some(_url) => page += 1,
+                None => break,
+            }
+        }
+
+        Ok(issues)
+    }
+
+    async fn set_issue_milestone(
+        &self,
+        org: &str,
+        repo: &str,
+        number: u64,
+        milestone: Option<i64>,
+    ) -> crate::error::Result<()> {
+        let _resp: Value = self
+            .patch(
+                &format!("/repos/{}/{}/issues/{}", org, repo, number),
+                Some(&json!({
+                    "milestone": milestone,
+                })),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn get_ref(
+        &self,
+        org: &str,
+        repo: &str,
+        name: &str,
+    ) -> crate::error::Result<Option<Reference>> {
+        let resp: std::result::Result<GitRef, octocrab::Error> = self
+            .get(
+                format!("/repos/{}/{}/git/ref/{}", org, repo, name),
+                None::<&()>,
+            )
+            .await;
+
+        match resp {
+            Ok(git_ref) => Ok(Some(Reference {
+                name: git_ref.name,
+                sha: git_ref.object.sha,
+            })),
+            Err(error) if is_not_found(&error) => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    async fn resolve_commit(
+        &self,
+        org: &str,
+        repo: &str,
+        reference: &str,
+    ) -> crate::error::Result<Option<String>> {
+        let resp: std::result::Result<Value, octocrab::Error> = self
+            .get(
+                format!("/repos/{}/{}/commits/{}", org, repo, reference),
+                None::<&()>,
+            )
+            .await;
+
+        match resp {
+            Ok(commit) => Ok(commit["sha"].as_str().map(|sha| sha.to_string())),
+            Err(error) if is_not_found(&error) => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    async fn create_ref(
+        &self,
+        org: &str,
+        repo: &str,
+        reference: &Reference,
+    ) -> crate::error::Result<Reference> {
+        let resp: GitRef = self
+            .post(
+                format!("/repos/{}/{}/git/refs", org, repo),
+                Some(&json!({
+                    "ref": reference.name,
+                    "sha": reference.sha,
+                })),
+            )
+            .await?;
+        Ok(Reference {
+            name: resp.name,
+            sha: resp.object.sha,
+        })
+    }
+
+    async fn list_tags(&self, org: &str, repo: &str) -> crate::error::Result<Vec<Reference>> {
+        let resp: Vec<GitRef> = self
+            .get(
+                format!("/repos/{}/{}/git/matching-refs/tags", org, repo),
+                None::<&()>,
+            )
+            .await?;
+
+        Ok(resp
+            .into_iter()
+            .map(|git_ref| Reference {
+                name: git_ref.name,
+                sha: git_ref.object.sha,
+            })
+            .collect())
+    }
+
+    async fn list_branches(&self, org: &str, repo: &str) -> crate::error::Result<Vec<Reference>> {
+        let resp: Vec<GitRef> = self
+            .get(
+                format!("/repos/{}/{}/git/matching-refs/heads", org, repo),
+                None::<&()>,
+            )
+            .await?;
+
+        Ok(resp
+            .into_iter()
+            .map(|git_ref| Reference {
+                name: git_ref.name,
+                sha: git_ref.object.sha,
+            })
+            .collect())
+    }
+
+    async fn list_merged_pull_requests(
+        &self,
+        org: &str,
+        repo: &str,
+        base: &str,
+        head: &str,
+    ) -> crate::error::Result<Vec<PullRequest>> {
+        let shas = match base {
+            "" => list_commits(self, org, repo, head).await?,
+            base => compare_commits(self, org, repo, base, head).await?,
+        };
+
+        // Pull requests are matched by the commit they were merged as, listing
+        // them once costs a request per 100 pull requests instead of one per
+        // commit
+        let mut merged = list_merged(self, org, repo).await?;
+
+        let mut seen: HashSet<u64> = HashSet::new();
+        let mut pull_requests: Vec<PullRequest> = vec![];
+        for sha in shas {
+            let Some(pull_request) = merged.remove(&sha) else {
+                continue;
+            };
+            if seen.insert(pull_request.number) {
+                pull_requests.push(pull_request);
+            }
+        }
+
+        Ok(pull_requests)
+    }
+
+    async fn get_release(
+        &self,
+        org: &str,
+        repo: &str,
+        tag: &str,
+    ) -> crate::error::Result<Option<Release>> {
+        let resp: std::result::Result<Release, octocrab::Error> = self
+            .get(
+                format!("/repos/{}/{}/releases/tags/{}", org, repo, tag),
+                None::<&()>,
+            )
+            .await;
+
+        match resp {
+            Ok(release) => Ok(Some(release)),
+            Err(error) if is_not_found(&error) => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    async fn get_latest_release(
+        &self,
+        org: &str,
+        repo: &str,
+    ) -> crate::error::Result<Option<Release>> {
+        let resp: std::result::Result<Release, octocrab::Error> = self
+            .get(
+                format!("/repos/{}/{}/releases/latest", org, repo),
+                None::<&()>,
+            )
+            .await;
+
+        match resp {
+            Ok(release) => Ok(Some(release)),
+            Err(error) if is_not_found(&error) => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    async fn generate_release_notes(
+        &self,
+        org: &str,
+        repo: &str,
+        tag: &str,
+        target: &str,
+        previous: Option<&str>,
+    ) -> crate::error::Result<String> {
+        let resp: GeneratedNotes = self
+            .post(
+                format!("/repos/{}/{}/releases/generate-notes", org, repo),
+                Some(&json!({
+                    "tag_name": tag,
+                    "target_commitish": target,
+                    "previous_tag_name": previous,
+                })),
+            )
+            .await?;
+        Ok(resp.body)
+    }
+
+    async fn create_release(
+        &self,
+        org: &str,
+        repo: &str,
+        release: &Release,
+    ) -> crate::error::Result<Release> {
+        let resp: Release = self
+            .post(format!("/repos/{}/{}/releases", org, repo), Some(release))
+            .await?;
+        Ok(resp)
+    }
+
+    async fn list_comments(
+        &self,
+        org: &str,
+        repo: &str,
+        number: u64,
+    ) -> crate::error::Result<Vec<Comment>> {
+        let mut comments: Vec<Comment> = vec![];
+
+        let mut page: u32 = 1;
+        loop {
+            let resp = self
+                .issues(org, repo)
+                .list_comments(number)
+                .page(page)
+                .per_page(100)
+                .send()
+                .await?;
+
+            let items = resp.items;
+            comments.extend(items.into_iter().map(Comment::from));
+
+            match resp.next {
+                Some(_url) => page += 1,
+                None => break,
+            }
+        }
+
+        Ok(comments)
+    }
+
+    async fn create_comment(
+        &self,
+        org: &str,
+        repo: &str,
+        number: u64,
+        body: &str,
+    ) -> crate::error::Result<Comment> {
+        let resp = self.issues(org, repo).create_comment(number, body).await?;
+        Ok(resp.into())
+    }
+
+    async fn update_comment(
+        &self,
+        org: &str,
+        repo: &str,
+        id: u64,
+        body: &str,
+    ) -> crate::error::Result<Comment> {
+        let resp = self
+            .issues(org, repo)
+            .update_comment(CommentId(id), body)
+            .await?;
+        Ok(resp.into())
+    }
+
+    async fn delete_comment(&self, org: &str, repo: &str, id: u64) -> crate::error::Result<()> {
+        self.issues(org, repo).delete_comment(CommentId(id)).await?;
+        Ok(())
+    }
+
+    async fn create_check_run(
+        &self,
+        org: &str,
+        repo: &str,
+        check_run: &CheckRun,
+    ) -> crate::error::Result<()> {
+        let _resp: Value = self
+            .post(
+                format!("/repos/{}/{}/check-runs", org, repo),
+                Some(&json!({
+                    "name": check_run.name,
+                    "head_sha": check_run.head_sha,
+                    "status": "completed",
+                    "conclusion": check_run.conclusion,
+                    "output": check_run.output,
+                })),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn list_issue_labels(
+        &self,
+        org: &str,
+        repo: &str,
+        number: u64,
+    ) -> crate::error::Result<Vec<String>> {
+        let mut labels: Vec<String> = vec![];
+
+        let mut page: u32 = 1;
+        loop {
+            let resp = self
+                .issues(org, repo)
+                .list_labels_for_issue(number)
+                .page(page)
+                .per_page(100)
+                .send()
+                .await?;
+
+            let items = resp.items;
+            labels.extend(items.into_iter().map(|label| label.name));
+
+            match resp.next {
+                Some(_url) => page += 1,
+                None => break,
+            }
+        }
+
+        Ok(labels)
+    }
+
+    async fn add_issue_labels(
+        &self,
+        org: &str,
+        repo: &str,
+        number: u64,
+        labels: &[String],
+    ) -> crate::error::Result<()> {
+        self.issues(org, repo).add_labels(number, labels).await?;
+        Ok(())
+    }
+
+    async fn remove_issue_label(
+        &self,
+        org: &str,
+        repo: &str,
+        number: u64,
+        name: &str,
+    ) -> crate::error::Result<()> {
+        self.issues(org, repo).remove_label(number, name).await?;
+        Ok(())
+    }
+
+    async fn update_issue(
+        &self,
+        org: &str,
+        repo: &str,
+        number: u64,
+        issue: &IssuePayload,
+    ) -> crate::error::Result<()> {
+        let _resp: Value = self
+            .patch(
+                &format!("/repos/{}/{}/issues/{}", org, repo, number),
+                Some(issue),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn add_assignees(
+        &self,
+        org: &str,
+        repo: &str,
+        number: u64,
+        assignees: &[String],
+    ) -> crate::error::Result<()> {
+        let _resp: Value = self
+            .post(
+                format!("/repos/{}/{}/issues/{}/assignees", org, repo, number),
+                Some(&json!({
+                    "assignees": assignees,
+                })),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn remove_assignees(
+        &self,
+        org: &str,
+        repo: &str,
+        number: u64,
+        assignees: &[String],
+    ) -> crate::error::Result<()> {
+        let _resp: Value = self
+            .delete(
+                format!("/repos/{}/{}/issues/{}/assignees", org, repo, number),
+                Some(&json!({
+                    "assignees": assignees,
+                })),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn get_permission(
+        &self,
+        org: &str,
+        repo: &str,
+        user: &str,
+    ) -> crate::error::Result<String> {
+        let resp: std::result::Result<Value, octocrab::Error> = self
+            .get(
+                format!("/repos/{}/{}/collaborators/{}/permission", org, repo, user),
+                None::<&()>,
+            )
+            .await;
+
+        match resp {
+            Ok(permission) => Ok(permission["role_name"]
+                .as_str()
+                .unwrap_or("none")
+                .to_string()),
+            Err(error) if is_not_found(&error) => Ok("none".to_string()),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    async fn get_pull_request(
+        &self,
+        org: &str,
+        repo: &str,
+        number: u64,
+    ) -> crate::error::Result<PullRequestDetails> {
+        let resp = self.pulls(org, repo).get(number).await?;
+
+        Ok(PullRequestDetails {
+            number: resp.number,
+            title: resp.title.unwrap_or_default(),
+            author: resp.user.map(|user| user.login),
+            base: resp.base.ref_field,
+            head: resp.head.ref_field,
+            head_sha: resp.head.sha,
+            commits: resp.commits.unwrap_or_default(),
+            merged: resp.merged_at.is_some(),
+            merge_commit_sha: resp.merge_commit_sha,
+            labels: resp
+                .labels
+                .unwrap_or_default()
+                .into_iter()
+                .map(|label| label.name)
+                .collect(),
+        })
+    }
+
+    async fn find_pull_request(
+        &self,
+        org: &str,
+        repo: &str,
+        head: &str,
+    ) -> crate::error::Result<Option<PullRequest>> {
+        let resp: Vec<octocrab::models::pulls::PullRequest> = self
+            .get(
+                format!("/repos/{}/{}/pulls", org, repo),
+                Some(&json!({
+                    "head": format!("{}:{}", org, head),
+                    "state": "all",
+                })),
+            )
+            .await?;
+
+        Ok(resp.into_iter().next().map(|pull_request| PullRequest {
+            number: pull_request.number,
+            title: pull_request.title.unwrap_or_default(),
+            author: pull_request.user.map(|user| user.login),
+            url: pull_request.html_url.map(|url| url.to_string()),
+        }))
+    }
+
+    async fn create_pull_request(
+        &self,
+        org: &str,
+        repo: &str,
+        pull_request: &PullRequestPayload,
+    ) -> crate::error::Result<PullRequest> {
+        let resp: octocrab::models::pulls::PullRequest = self
+            .post(format!("/repos/{}/{}/pulls", org, repo), Some(pull_request))
+            .await?;
+
+        Ok(PullRequest {
+            number: resp.number,
+            title: resp.title.unwrap_or_default(),
+            author: resp.user.map(|user| user.login),
+            url: resp.html_url.map(|url| url.to_string()),
+        })
+    }
+}
+
+// list_milestones fetches every page of the milestones in the given state.
+async fn list_milestones(
+    client: &Octocrab,
+    org: &str,
+    repo: &str,
+    state: &str,
+) -> crate::error::Result<Vec<Milestone>> {
+    let mut current_milestones: Vec<Milestone> = vec![];
+
+    let mut page: Page<octocrab::models::Milestone> = client
+        .get(
+            format!("/repos/{}/{}/milestones", org, repo),
+            Some(&json!({ "state": state })),
+        )
+        .await?;
+
+    let items = page.items;
+    current_milestones.extend(items.into_iter().map(Milestone::from));
+
+    while let Some(next_page) = client
+        .get_page::<octocrab::models::Milestone>(&page.next)
+        .await?
+    {
+        page = next_page;
+
+        let items = page.items;
+        current_milestones.extend(items.into_iter().map(Milestone::from));
+    }
+
+    Ok(current_milestones)
+}
+
+// compare_commits returns the commits in base...head, oldest first.
+async fn compare_commits(
+    client: &Octocrab,
+    org: &str,
+    repo: &str,
+    base: &str,
+    head: &str,
+) -> crate::error::Result<Vec<String>> {
+    let per_page = 100;
+
+    let mut shas: Vec<String> = vec![];
+    let mut page: u32 = 1;
+    loop {
+        let resp = client
+            .commits(org, repo)
+            .compare(base, head)
+            .page(page)
+            .per_page(per_page)
+            .send()
+            .await?;
+
+        let received = resp.commits.len();
+        shas.extend(resp.commits.into_iter().map(|commit| commit.sha));
+
+        if received < per_page as usize || shas.len() as i64 >= resp.total_commits {
+            break;
+        }
+        page += 1;
+    }
+    Ok(shas)
+}
+
+// list_merged returns the merged pull requests of the repository by the sha of
+// the commit they were merged as.
+async fn list_merged(
+    client: &Octocrab,
+    org: &str,
+    repo: &str,
+) -> crate::error::Result<HashMap<String, PullRequest>> {
+    let per_page = 100;
+
+    let mut merged: HashMap<String, PullRequest> = HashMap::new();
+    let mut page: u32 = 1;
+    loop {
+        let resp: Vec<octocrab::models::pulls::PullRequest> = client
+            .get(
+                format!(
+                    "/repos/{}/{}/pulls?state=closed&per_page={}&page={}",
+                    org, repo, per_page, page
+                ),
+                None::<&()>,
+            )
+            .await?;
+
+        let received = resp.len();
+        for pull_request in resp {
+            let (Some(_), Some(sha)) = (pull_request.merged_at, pull_request.merge_commit_sha)
+            else {
+                continue;
+            };
+            merged.insert(
+                sha,
+                PullRequest {
+                    number: pull_request.number,
+                    title: pull_request.title.unwrap_or_default(),
+                    author: pull_request.user.map(|user| user.login),
+                    url: pull_request.html_url.map(|url| url.to_string()),
+                },
+            );
+        }
+
+        if received < per_page {
+            break;
+        }
+        page += 1;
+    }
+    Ok(merged)
+}
+
+// list_commits returns every commit of head, oldest first.
+async fn list_commits(
+    client: &Octocrab,
+    org: &str,
+    repo: &str,
+    head: &str,
+) -> crate::error::Result<Vec<String>> {
+    let per_page = 100;
+
+    let mut shas: Vec<String> = vec![];
+    let mut page: u32 = 1;
+    loop {
+        let resp: Vec<GitObject> = client
+            .get(
+                format!(
+                    "/repos/{}/{}/commits?sha={}&per_page={}&page={}",
+                    org, repo, head, per_page, page
+                ),
+                None::<&()>,
+            )
+            .await?;
+
+        let received = resp.len();
+        shas.extend(resp.into_iter().map(|commit| commit.sha));
+
+        if received < per_page {
+            break;
+        }
+        page += 1;
+    }
+
+    // GitHub lists the newest commits first
+    shas.reverse();
+    Ok(shas)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        thread::{self, JoinHandle},
+    };
+
+    use octocrab::Octocrab;
+
+    use crate::{
+        client::label_route,
+        github::{GitHub, Label},
+    };
+
+    // serve answers the requests with the responses in order, it returns the
+    // request lines, e.g. `DELETE /repos/org/repo/labels/kind%2Fbug HTTP/1.1`
+    fn serve(responses: Vec<(&'static str, String)>) -> (String, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let uri = format!("http://{}", listener.local_addr().unwrap());
+
+        let handle = thread::spawn(move || {
+            responses
+                .into_iter()
+                .map(|(status, body)| {
+                    let (mut stream, _) = listener.accept().unwrap();
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+                    let mut request_line = String::new();
+                    reader.read_line(&mut request_line).unwrap();
+                    let mut length = 0;
+                    loop {
+                        let mut header = String::new();
+                        reader.read_line(&mut header).unwrap();
+                        if header == "\r\n" {
+                            break;
+                        }
+                        if let Some(value) = header.to_lowercase().strip_prefix("content-length:") {
+                            length = value.trim().parse().unwrap();
+                        }
+                    }
+                    reader.read_exact(&mut vec![0; length]).unwrap();
+
+                    write!(
+                        stream,
+                        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        status,
+                        body.len(),
+                        body
+                    )
+                    .unwrap();
+                    request_line.trim_end().to_string()
+                })
+                .collect()
+        });
+        (uri, handle)
+    }
+
+    // Labels generated from templates, e.g. `cherry-pick/{branch}`, hold a
+    // slash which GitHub reads as another route when it is not encoded.
+    #[tokio::test]
+    async fn slashed_labels() {
+        let label = Label {
+            name: "cherry-pick/release-0.2".to_string(),
+            color: "fef2a0".to_string(),
+            description: Some("Cherry-pick to release-0.2.".to_string()),
+        };
+        let (uri, server) = serve(vec![
+            (
+                "200 OK",
+                serde_json::json!({
+                    "id": 1,
+                    "node_id": "L_1",
+                    "url": "https://api.github.com/repos/org/repo/labels/cherry-pick%2Frelease-0.2",
+                    "name": label.name,
+                    "description": label.description,
+                    "color": label.color,
+                    "default": false,
+                })
+                .to_string(),
+            ),
+            ("204 No Content", String::new()),
+        ]);
+        let client = Octocrab::builder().base_uri(uri).unwrap().build().unwrap();
+
+        let updated = client
+            .update_label("org", "repo", &label.name, &label)
+            .await
+            .unwrap();
+        assert_eq!(label.name, updated.name);
+        client
+            .delete_label("org", "repo", "cherry-pick/release-0.1")
+            .await
+            .unwrap();
+
+        assert_eq!(
+            vec![
+                "PATCH /repos/org/repo/labels/cherry%2Dpick%2Frelease%2D0%2E2 HTTP/1.1",
+                "DELETE /repos/org/repo/labels/cherry%2Dpick%2Frelease%2D0%2E1 HTTP/1.1",
+            ],
+            server.join().unwrap()
+        );
+    }
+
+    // pull_request is a closed pull request as listed by GitHub, merged as
+    // the commit with the sha when there is one
+    fn pull_request(number: u64, sha: Option<&str>) -> serde_json::Value {
+        serde_json::json!({
+            "url": format!("https://api.github.com/repos/org/repo/pulls/{number}"),
+            "id": number,
+            "html_url": format!("https://github.com/org/repo/pull/{number}"),
+            "number": number,
+            "title": format!("Pull request {number}"),
+            "user": null,
+            "merged_at": sha.map(|_| "2024-01-01T00:00:00Z"),
+            "merge_commit_sha": sha,
+            "head": {"ref": "feature", "sha": "f"},
+            "base": {"ref": "main", "sha": "m"},
+        })
+    }
+
+    // The merged pull requests are listed once, not looked up commit by
+    // commit.
+    #[tokio::test]
+    async fn merged_pull_requests() {
+        let (uri, server) = serve(vec![
+            (
+                "200 OK",
+                serde_json::json!([{"sha": "c3"}, {"sha": "c2"}, {"sha": "c1"}]).to_string(),
+            ),
+            (
+                "200 OK",
+                serde_json::json!([
+                    pull_request(4, None),
+                    pull_request(3, Some("c3")),
+                    pull_request(1, Some("c1")),
+                    pull_request(0, Some("c0")),
+                ])
+                .to_string(),
+            ),
+        ]);
+        let client = Octocrab::builder().base_uri(uri).unwrap().build().unwrap();
+
+        let pull_requests = client
+            .list_merged_pull_requests("org", "repo", "", "main")
+            .await
+            .unwrap();
+        assert_eq!(
+            vec![(1, "Pull request 1"), (3, "Pull request 3")],
+            pull_requests
+                .iter()
+                .map(|pull_request| (pull_request.number, pull_request.title.as_str()))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            Some("https://github.com/org/repo/pull/1"),
+            pull_requests[0].url.as_deref()
+        );
+
+        assert_eq!(
+            vec![
+                "GET /repos/org/repo/commits?sha=main&per_page=100&page=1 HTTP/1.1",
+                "GET /repos/org/repo/pulls?state=closed&per_page=100&page=1 HTTP/1.1",
+            ],
+            server.join().unwrap()
+        );
+    }
+
+    #[test]
+    fn label_routes() {
+        assert_eq!(
+            "/repos/org/repo/labels/good%20first%20issue",
+            label_route("org", "repo", "good first issue")
+        );
+        assert_eq!(
+            "/repos/org/repo/labels/cherry%2Dpick%2Frelease%2D0%2E1",
+            label_route("org", "repo", "cherry-pick/release-0.1")
+        );
+        assert_eq!(
+            "/repos/org/repo/labels/kind%2Fbug",
+            label_route("org", "repo", "kind/bug")
+        );
+    }
+}
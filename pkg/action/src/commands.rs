@@ -1,19 +1,306 @@
+use std::{
+    env, fmt,
+    fs::OpenOptions,
+    io::Write,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::error::{Error, Result};
+
+// ActionCommand is a workflow command, the runner reads it from stdout.
+// https://docs.github.com/en/actions/writing-workflows/choosing-what-your-workflow-does/workflow-commands-for-github-actions
 pub enum ActionCommand {
     Debug(String),
     Notice(String),
     Warning(String),
     Error(String),
+    // Level, Properties, Message
+    Annotation(Level, Properties, String),
+    // Title, the lines printed until EndGroup are folded under it
+    Group(String),
+    EndGroup,
+    // Value, replaced by *** in the logs from now on
+    AddMask(String),
+}
+
+// Level of an annotation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Level {
+    Notice,
+    Warning,
+    Error,
+}
+
+// Properties tell where an annotation points to, lines and columns are
+// 1-based.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Properties {
+    pub title: Option<String>,
+    pub file: Option<String>,
+    pub line: Option<usize>,
+    pub end_line: Option<usize>,
+    pub col: Option<usize>,
+    pub end_column: Option<usize>,
+}
+
+impl Properties {
+    pub fn file(file: &str) -> Self {
+        Self {
+            file: Some(file.to_string()),
+            ..Default::default()
+        }
+    }
+
+    pub fn at(self, line: usize, col: usize) -> Self {
+        Self {
+            line: Some(line),
+            col: Some(col),
+            ..self
+        }
+    }
+
+    pub fn title(self, title: &str) -> Self {
+        Self {
+            title: Some(title.to_string()),
+            ..self
+        }
+    }
+
+    fn pairs(&self) -> Vec<(&'static str, String)> {
+        let number = |n: &Option<usize>| n.map(|n| n.to_string());
+        [
+            ("title", self.title.clone()),
+            ("file", self.file.clone()),
+            ("line", number(&self.line)),
+            ("endLine", number(&self.end_line)),
+            ("col", number(&self.col)),
+            ("endColumn", number(&self.end_column)),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| value.map(|value| (key, value)))
+        .collect()
+    }
 }
 
 impl ActionCommand {
     pub fn send_command(&self) {
-        let (command, message) = match self {
-            Self::Debug(message) => ("debug", message),
-            Self::Notice(message) => ("notice", message),
-            Self::Warning(message) => ("warning", message),
-            Self::Error(message) => ("error", message),
+        println!("{}", self);
+    }
+}
+
+impl fmt::Display for ActionCommand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let none = Properties::default();
+        let (command, properties, message) = match self {
+            Self::Debug(message) => ("debug", &none, message.as_str()),
+            Self::Notice(message) => ("notice", &none, message.as_str()),
+            Self::Warning(message) => ("warning", &none, message.as_str()),
+            Self::Error(message) => ("error", &none, message.as_str()),
+            Self::Annotation(level, properties, message) => {
+                let command = match level {
+                    Level::Notice => "notice",
+                    Level::Warning => "warning",
+                    Level::Error => "error",
+                };
+                (command, properties, message.as_str())
+            }
+            Self::Group(title) => ("group", &none, title.as_str()),
+            Self::EndGroup => ("endgroup", &none, ""),
+            Self::AddMask(value) => ("add-mask", &none, value.as_str()),
         };
 
-        println!("::{}::{}", command, message);
+        write!(f, "::{}", command)?;
+        let pairs: Vec<String> = properties
+            .pairs()
+            .into_iter()
+            .map(|(key, value)| format!("{}={}", key, escape_property(&value)))
+            .collect();
+        if !pairs.is_empty() {
+            write!(f, " {}", pairs.join(","))?;
+        }
+        write!(f, "::{}", escape_data(message))
+    }
+}
+
+// escape_data keeps a message on a single line, the runner unescapes it.
+pub fn escape_data(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+// escape_property also escapes the separators of the properties.
+pub fn escape_property(value: &str) -> String {
+    escape_data(value).replace(':', "%3A").replace(',', "%2C")
+}
+
+// EnvFile is a file the runner reads once the step is done, its path is in
+// an environment variable of the same name.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EnvFile {
+    Output,
+    Env,
+    StepSummary,
+}
+
+impl EnvFile {
+    pub fn variable(&self) -> &'static str {
+        match self {
+            Self::Output => "GITHUB_OUTPUT",
+            Self::Env => "GITHUB_ENV",
+            Self::StepSummary => "GITHUB_STEP_SUMMARY",
+        }
+    }
+
+    // path is None outside of a workflow run
+    pub fn path(&self) -> Option<String> {
+        env::var(self.variable())
+            .ok()
+            .filter(|path| !path.is_empty())
+    }
+
+    // append writes the content to the file, or prints it when running
+    // outside of a workflow so it can still be read.
+    pub fn append(&self, content: &str) -> Result<()> {
+        match self.path() {
+            Some(path) => append_to(&path, content),
+            None => {
+                print!("{}", content);
+                Ok(())
+            }
+        }
+    }
+}
+
+pub fn append_to(path: &str, content: &str) -> Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(content.as_bytes())?;
+    Ok(())
+}
+
+// set_output sets an output of the step, later steps read it from
+// `steps.<id>.outputs.<name>`.
+pub fn set_output(name: &str, value: &str) -> Result<()> {
+    EnvFile::Output.append(&key_value(name, value, &delimiter(value))?)
+}
+
+// export_variable sets an environment variable for the later steps of the
+// job.
+pub fn export_variable(name: &str, value: &str) -> Result<()> {
+    EnvFile::Env.append(&key_value(name, value, &delimiter(value))?)
+}
+
+// append_summary adds markdown to the summary of the job.
+pub fn append_summary(markdown: &str) -> Result<()> {
+    match markdown.ends_with('\n') {
+        true => EnvFile::StepSummary.append(markdown),
+        false => EnvFile::StepSummary.append(&format!("{}\n", markdown)),
+    }
+}
+
+// key_value formats an entry of $GITHUB_OUTPUT or $GITHUB_ENV, values with
+// line breaks are written between delimiters.
+pub fn key_value(name: &str, value: &str, delimiter: &str) -> Result<String> {
+    if name.is_empty() || name.contains(['=', '\r', '\n']) || name.contains("<<") {
+        return Err(Error::Invalid(format!("name {:?}", name)));
+    }
+
+    if !value.contains(['\r', '\n']) {
+        return Ok(format!("{}={}\n", name, value));
+    }
+    if value.lines().any(|line| line == delimiter) {
+        return Err(Error::Invalid(format!(
+            "value of {:?} contains the delimiter {:?}",
+            name, delimiter
+        )));
+    }
+    Ok(format!(
+        "{}<<{}\n{}\n{}\n",
+        name, delimiter, value, delimiter
+    ))
+}
+
+// delimiter returns a delimiter that is not a line of the value
+fn delimiter(value: &str) -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+
+    let mut delimiter = format!("ghadelimiter_{}_{}", std::process::id(), nanos);
+    while value.lines().any(|line| line == delimiter) {
+        delimiter.push('_');
+    }
+    delimiter
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::commands::{append_to, key_value, ActionCommand, Level, Properties};
+
+    #[test]
+    fn commands() {
+        assert_eq!(
+            "::warning::50%25 done%0D%0Anext line",
+            ActionCommand::Warning("50% done\r\nnext line".to_string()).to_string()
+        );
+        assert_eq!(
+            "::error title=Invalid%2C see docs,file=pkg/config/config.yaml,line=3,col=12::labels[0].color: \"red\" is not a hex color",
+            ActionCommand::Annotation(
+                Level::Error,
+                Properties::file("pkg/config/config.yaml")
+                    .at(3, 12)
+                    .title("Invalid, see docs"),
+                "labels[0].color: \"red\" is not a hex color".to_string()
+            )
+            .to_string()
+        );
+        assert_eq!(
+            "::notice file=a%3Ab.yaml::ok",
+            ActionCommand::Annotation(
+                Level::Notice,
+                Properties::file("a:b.yaml"),
+                "ok".to_string()
+            )
+            .to_string()
+        );
+        assert_eq!(
+            "::group::Plan",
+            ActionCommand::Group("Plan".to_string()).to_string()
+        );
+        assert_eq!("::endgroup::", ActionCommand::EndGroup.to_string());
+        assert_eq!(
+            "::add-mask::secret",
+            ActionCommand::AddMask("secret".to_string()).to_string()
+        );
+    }
+
+    #[test]
+    fn outputs() {
+        assert_eq!(
+            "version=v1.2.3\n",
+            key_value("version", "v1.2.3", "EOF").unwrap()
+        );
+        assert_eq!("previous=\n", key_value("previous", "", "EOF").unwrap());
+        assert_eq!(
+            "notes<<EOF\n## Changes\n- one\n\nEOF\n",
+            key_value("notes", "## Changes\n- one\n", "EOF").unwrap()
+        );
+        assert!(key_value("a=b", "c", "EOF").is_err());
+        assert!(key_value("", "c", "EOF").is_err());
+        assert!(key_value("notes", "one\nEOF\ntwo", "EOF").is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("output");
+        let path = path.to_str().unwrap();
+        append_to(path, &key_value("version", "v1.2.3", "EOF").unwrap()).unwrap();
+        append_to(path, &key_value("notes", "a\nb", "EOF").unwrap()).unwrap();
+        assert_eq!(
+            "version=v1.2.3\nnotes<<EOF\na\nb\nEOF\n",
+            fs::read_to_string(path).unwrap()
+        );
     }
 }
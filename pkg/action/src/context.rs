@@ -8,6 +8,7 @@ pub struct GitHubVariables {
     pub github_actions: bool,
     pub github_event_name: String,
     pub github_event_path: String,
+    pub github_repository: String,
 }
 
 impl GitHubVariables {
@@ -15,4 +16,9 @@ impl GitHubVariables {
         let result = envy::from_env::<GitHubVariables>()?;
         Ok(result)
     }
+
+    // repository splits GITHUB_REPOSITORY into org and repo
+    pub fn repository(&self) -> Option<(&str, &str)> {
+        self.github_repository.split_once('/')
+    }
 }
@@ -7,4 +7,16 @@ pub enum Error {
 
     #[error(transparent)]
     Octocrab(#[from] octocrab::Error),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error("Not found: {0}")]
+    NotFound(String),
+
+    #[error("Already exists: {0}")]
+    AlreadyExists(String),
+
+    #[error("Invalid: {0}")]
+    Invalid(String),
 }
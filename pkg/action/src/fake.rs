@@ -0,0 +1,638 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use async_trait::async_trait;
+
+use crate::{
+    error::{Error, Result},
+    github::{
+        CheckRun, Comment, GitHub, Issue, IssuePayload, Label, Milestone, MilestonePayload,
+        PullRequest, PullRequestDetails, PullRequestPayload, Reference, Release,
+    },
+};
+
+// FakeRepo is the state of a single repository kept by `FakeGitHub`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FakeRepo {
+    pub labels: Vec<Label>,
+    pub milestones: Vec<Milestone>,
+    pub issues: Vec<Issue>,
+    pub refs: Vec<Reference>,
+
+    // Merged pull requests by the compared range, e.g. `v0.1.0...main`
+    pub compare: HashMap<String, Vec<PullRequest>>,
+    pub releases: Vec<Release>,
+
+    // Notes GitHub generates by release tag
+    pub generated_notes: HashMap<String, String>,
+
+    // Comments by issue or pull request number
+    pub comments: HashMap<u64, Vec<Comment>>,
+    pub check_runs: Vec<CheckRun>,
+
+    // Label names by issue or pull request number
+    pub issue_labels: HashMap<u64, Vec<String>>,
+
+    // Logins by issue or pull request number
+    pub assignees: HashMap<u64, Vec<String>>,
+
+    // Roles by login, users without one have `none`
+    pub permissions: HashMap<String, String>,
+
+    // Pull requests, the created ones are added
+    pub pull_requests: Vec<PullRequestDetails>,
+}
+
+// FakeGitHub is an in-memory implementation of `GitHub` for offline tests.
+#[derive(Default)]
+pub struct FakeGitHub {
+    repos: Mutex<HashMap<String, FakeRepo>>,
+}
+
+impl FakeGitHub {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_repo(self, org: &str, repo: &str, state: FakeRepo) -> Self {
+        self.repos
+            .lock()
+            .unwrap()
+            .insert(format!("{org}/{repo}"), state);
+        self
+    }
+
+    // repo returns a snapshot of the repository to assert against
+    pub fn repo(&self, org: &str, repo: &str) -> FakeRepo {
+        self.repos
+            .lock()
+            .unwrap()
+            .get(&format!("{org}/{repo}"))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn with<T>(
+        &self,
+        org: &str,
+        repo: &str,
+        f: impl FnOnce(&mut FakeRepo) -> Result<T>,
+    ) -> Result<T> {
+        let mut repos = self.repos.lock().unwrap();
+        let state = repos
+            .get_mut(&format!("{org}/{repo}"))
+            .ok_or_else(|| Error::NotFound(format!("{org}/{repo}")))?;
+        f(state)
+    }
+}
+
+#[async_trait]
+impl GitHub for FakeGitHub {
+    async fn list_labels(&self, org: &str, repo: &str) -> Result<Vec<Label>> {
+        self.with(org, repo, |state| Ok(state.labels.clone()))
+    }
+
+    async fn create_label(&self, org: &str, repo: &str, label: &Label) -> Result<Label> {
+        self.with(org, repo, |state| {
+            if state
+                .labels
+                .iter()
+                .any(|l| l.name.eq_ignore_ascii_case(&label.name))
+            {
+                return Err(Error::AlreadyExists(label.name.clone()));
+            }
+            state.labels.push(label.clone());
+            Ok(label.clone())
+        })
+    }
+
+    async fn update_label(
+        &self,
+        org: &str,
+        repo: &str,
+        name: &str,
+        label: &Label,
+    ) -> Result<Label> {
+        self.with(org, repo, |state| {
+            let current = state
+                .labels
+                .iter_mut()
+                .find(|l| l.name.eq_ignore_ascii_case(name))
+                .ok_or_else(|| Error::NotFound(name.to_string()))?;
+            *current = label.clone();
+            Ok(label.clone())
+        })
+    }
+
+    async fn delete_label(&self, org: &str, repo: &str, name: &str) -> Result<()> {
+        self.with(org, repo, |state| {
+            let len = state.labels.len();
+            state.labels.retain(|l| !l.name.eq_ignore_ascii_case(name));
+            match state.labels.len() < len {
+                true => Ok(()),
+                false => Err(Error::NotFound(name.to_string())),
+            }
+        })
+    }
+
+    async fn list_milestones(&self, org: &str, repo: &str) -> Result<Vec<Milestone>> {
+        self.with(org, repo, |state| {
+            Ok(state
+                .milestones
+                .iter()
+                .filter(|m| m.state.as_deref().unwrap_or("open") == "open")
+                .cloned()
+                .collect())
+        })
+    }
+
+    async fn list_closed_milestones(&self, org: &str, repo: &str) -> Result<Vec<Milestone>> {
+        self.with(org, repo, |state| {
+            Ok(state
+                .milestones
+                .iter()
+                .filter(|m| m.state.as_deref() == Some("closed"))
+                .cloned()
+                .collect())
+        })
+    }
+
+    async fn create_milestone(
+        &self,
+        org: &str,
+        repo: &str,
+        milestone: &MilestonePayload,
+    ) -> Result<Milestone> {
+        self.with(org, repo, |state| {
+            if state.milestones.iter().any(|m| m.title == milestone.title) {
+                return Err(Error::AlreadyExists(milestone.title.clone()));
+            }
+
+            let number = state.milestones.iter().map(|m| m.number).max().unwrap_or(0) + 1;
+            let created = Milestone {
+                number,
+                title: milestone.title.clone(),
+                description: milestone.description.clone(),
+                state: Some(milestone.state.clone().unwrap_or("open".to_string())),
+                due_on: parse_due_on(&milestone.due_on)?,
+                open_issues: Some(0),
+            };
+            state.milestones.push(created.clone());
+            Ok(created)
+        })
+    }
+
+    async fn update_milestone(
+        &self,
+        org: &str,
+        repo: &str,
+        number: i64,
+        milestone: &MilestonePayload,
+    ) -> Result<Milestone> {
+        self.with(org, repo, |state| {
+            let current = state
+                .milestones
+                .iter_mut()
+                .find(|m| m.number == number)
+                .ok_or_else(|| Error::NotFound(format!("milestone {number}")))?;
+
+            current.title = milestone.title.clone();
+            if milestone.description.is_some() {
+                current.description = milestone.description.clone();
+            }
+            if milestone.state.is_some() {
+                current.state = milestone.state.clone();
+            }
+            if milestone.due_on.is_some() {
+                current.due_on = parse_due_on(&milestone.due_on)?;
+            }
+            Ok(current.clone())
+        })
+    }
+
+    async fn list_milestone_issues(
+        &self,
+        org: &str,
+        repo: &str,
+        milestone: i64,
+    ) -> Result<Vec<Issue>> {
+        self.with(org, repo, |state| {
+            Ok(state
+                .issues
+                .iter()
+                .filter(|i| i.milestone == Some(milestone) && i.state == "open")
+                .cloned()
+                .collect())
+        })
+    }
+
+    async fn set_issue_milestone(
+        &self,
+        org: &str,
+        repo: &str,
+        number: u64,
+        milestone: Option<i64>,
+    ) -> Result<()> {
+        self.with(org, repo, |state| {
+            let issue = state
+                .issues
+                .iter_mut()
+                .find(|i| i.number == number)
+                .ok_or_else(|| Error::NotFound(format!("issue {number}")))?;
+            issue.milestone = milestone;
+
+            // Keep the open issue counters in sync, like GitHub does.
+            for m in state.milestones.iter_mut() {
+                let open_issues = state
+                    .issues
+                    .iter()
+                    .filter(|i| i.milestone == Some(m.number) && i.state == "open")
+                    .count();
+                m.open_issues = Some(open_issues as i64);
+            }
+            Ok(())
+        })
+    }
+
+    async fn get_ref(&self, org: &str, repo: &str, name: &str) -> Result<Option<Reference>> {
+        self.with(org, repo, |state| {
+            Ok(state
+                .refs
+                .iter()
+                .find(|r| r.name == format!("refs/{name}"))
+                .cloned())
+        })
+    }
+
+    async fn resolve_commit(
+        &self,
+        org: &str,
+        repo: &str,
+        reference: &str,
+    ) -> Result<Option<String>> {
+        self.with(org, repo, |state| {
+            let candidates = [
+                format!("refs/heads/{reference}"),
+                format!("refs/tags/{reference}"),
+            ];
+            Ok(state
+                .refs
+                .iter()
+                .find(|r| candidates.contains(&r.name) || r.sha == reference)
+                .map(|r| r.sha.clone()))
+        })
+    }
+
+    async fn create_ref(&self, org: &str, repo: &str, reference: &Reference) -> Result<Reference> {
+        self.with(org, repo, |state| {
+            if state.refs.iter().any(|r| r.name == reference.name) {
+                return Err(Error::AlreadyExists(reference.name.clone()));
+            }
+            state.refs.push(reference.clone());
+            Ok(reference.clone())
+        })
+    }
+
+    async fn list_tags(&self, org: &str, repo: &str) -> Result<Vec<Reference>> {
+        self.with(org, repo, |state| {
+            Ok(state
+                .refs
+                .iter()
+                .filter(|r| r.name.starts_with("refs/tags/"))
+                .cloned()
+                .collect())
+        })
+    }
+
+    async fn list_branches(&self, org: &str, repo: &str) -> Result<Vec<Reference>> {
+        self.with(org, repo, |state| {
+            Ok(state
+                .refs
+                .iter()
+                .filter(|r| r.name.starts_with("refs/heads/"))
+                .cloned()
+                .collect())
+        })
+    }
+
+    async fn list_merged_pull_requests(
+        &self,
+        org: &str,
+        repo: &str,
+        base: &str,
+        head: &str,
+    ) -> Result<Vec<PullRequest>> {
+        self.with(org, repo, |state| {
+            Ok(state
+                .compare
+                .get(&format!("{base}...{head}"))
+                .cloned()
+                .unwrap_or_default())
+        })
+    }
+
+    async fn get_release(&self, org: &str, repo: &str, tag: &str) -> Result<Option<Release>> {
+        self.with(org, repo, |state| {
+            Ok(state.releases.iter().find(|r| r.tag_name == tag).cloned())
+        })
+    }
+
+    async fn get_latest_release(&self, org: &str, repo: &str) -> Result<Option<Release>> {
+        self.with(org, repo, |state| {
+            Ok(state
+                .releases
+                .iter()
+                .rev()
+                .find(|r| !r.draft && !r.prerelease)
+                .cloned())
+        })
+    }
+
+    async fn generate_release_notes(
+        &self,
+        org: &str,
+        repo: &str,
+        tag: &str,
+        _target: &str,
+        _previous: Option<&str>,
+    ) -> Result<String> {
+        self.with(org, repo, |state| {
+            state
+                .generated_notes
+                .get(tag)
+                .cloned()
+                .ok_or_else(|| Error::NotFound(format!("generated notes of {tag}")))
+        })
+    }
+
+    async fn create_release(&self, org: &str, repo: &str, release: &Release) -> Result<Release> {
+        self.with(org, repo, |state| {
+            if state
+                .releases
+                .iter()
+                .any(|r| r.tag_name == release.tag_name)
+            {
+                return Err(Error::AlreadyExists(release.tag_name.clone()));
+            }
+            state.releases.push(release.clone());
+            Ok(release.clone())
+        })
+    }
+
+    async fn list_comments(&self, org: &str, repo: &str, number: u64) -> Result<Vec<Comment>> {
+        self.with(org, repo, |state| {
+            Ok(state.comments.get(&number).cloned().unwrap_or_default())
+        })
+    }
+
+    async fn create_comment(
+        &self,
+        org: &str,
+        repo: &str,
+        number: u64,
+        body: &str,
+    ) -> Result<Comment> {
+        self.with(org, repo, |state| {
+            let id = state
+                .comments
+                .values()
+                .flatten()
+                .map(|c| c.id)
+                .max()
+                .unwrap_or(0)
+                + 1;
+            let comment = Comment {
+                id,
+                body: body.to_string(),
+                author: Some("github-actions[bot]".to_string()),
+            };
+            state
+                .comments
+                .entry(number)
+                .or_default()
+                .push(comment.clone());
+            Ok(comment)
+        })
+    }
+
+    async fn update_comment(&self, org: &str, repo: &str, id: u64, body: &str) -> Result<Comment> {
+        self.with(org, repo, |state| {
+            let comment = state
+                .comments
+                .values_mut()
+                .flatten()
+                .find(|c| c.id == id)
+                .ok_or_else(|| Error::NotFound(format!("comment {id}")))?;
+            comment.body = body.to_string();
+            Ok(comment.clone())
+        })
+    }
+
+    async fn delete_comment(&self, org: &str, repo: &str, id: u64) -> Result<()> {
+        self.with(org, repo, |state| {
+            for comments in state.comments.values_mut() {
+                if let Some(position) = comments.iter().position(|c| c.id == id) {
+                    comments.remove(position);
+                    return Ok(());
+                }
+            }
+            Err(Error::NotFound(format!("comment {id}")))
+        })
+    }
+
+    async fn create_check_run(&self, org: &str, repo: &str, check_run: &CheckRun) -> Result<()> {
+        self.with(org, repo, |state| {
+            state.check_runs.push(check_run.clone());
+            Ok(())
+        })
+    }
+
+    async fn list_issue_labels(&self, org: &str, repo: &str, number: u64) -> Result<Vec<String>> {
+        self.with(org, repo, |state| {
+            Ok(state.issue_labels.get(&number).cloned().unwrap_or_default())
+        })
+    }
+
+    async fn add_issue_labels(
+        &self,
+        org: &str,
+        repo: &str,
+        number: u64,
+        labels: &[String],
+    ) -> Result<()> {
+        self.with(org, repo, |state| {
+            let current = state.issue_labels.entry(number).or_default();
+            for label in labels {
+                if !current.contains(label) {
+                    current.push(label.clone());
+                }
+            }
+            Ok(())
+        })
+    }
+
+    async fn remove_issue_label(
+        &self,
+        org: &str,
+        repo: &str,
+        number: u64,
+        name: &str,
+    ) -> Result<()> {
+        self.with(org, repo, |state| {
+            let current = state.issue_labels.entry(number).or_default();
+            let position = current
+                .iter()
+                .position(|l| l == name)
+                .ok_or_else(|| Error::NotFound(name.to_string()))?;
+            current.remove(position);
+            Ok(())
+        })
+    }
+
+    async fn update_issue(
+        &self,
+        org: &str,
+        repo: &str,
+        number: u64,
+        payload: &IssuePayload,
+    ) -> Result<()> {
+        self.with(org, repo, |state| {
+            let issue = state
+                .issues
+                .iter_mut()
+                .find(|i| i.number == number)
+                .ok_or_else(|| Error::NotFound(format!("issue {number}")))?;
+            if let Some(title) = &payload.title {
+                issue.title = title.clone();
+            }
+            if let Some(issue_state) = &payload.state {
+                issue.state = issue_state.clone();
+            }
+            Ok(())
+        })
+    }
+
+    async fn add_assignees(
+        &self,
+        org: &str,
+        repo: &str,
+        number: u64,
+        assignees: &[String],
+    ) -> Result<()> {
+        self.with(org, repo, |state| {
+            let current = state.assignees.entry(number).or_default();
+            for assignee in assignees {
+                if !current.contains(assignee) {
+                    current.push(assignee.clone());
+                }
+            }
+            Ok(())
+        })
+    }
+
+    async fn remove_assignees(
+        &self,
+        org: &str,
+        repo: &str,
+        number: u64,
+        assignees: &[String],
+    ) -> Result<()> {
+        self.with(org, repo, |state| {
+            let current = state.assignees.entry(number).or_default();
+            current.retain(|a| !assignees.contains(a));
+            Ok(())
+        })
+    }
+
+    async fn get_permission(&self, org: &str, repo: &str, user: &str) -> Result<String> {
+        self.with(org, repo, |state| {
+            Ok(state
+                .permissions
+                .get(user)
+                .cloned()
+                .unwrap_or_else(|| "none".to_string()))
+        })
+    }
+
+    async fn get_pull_request(
+        &self,
+        org: &str,
+        repo: &str,
+        number: u64,
+    ) -> Result<PullRequestDetails> {
+        self.with(org, repo, |state| {
+            state
+                .pull_requests
+                .iter()
+                .find(|p| p.number == number)
+                .cloned()
+                .ok_or_else(|| Error::NotFound(format!("pull request {number}")))
+        })
+    }
+
+    async fn find_pull_request(
+        &self,
+        org: &str,
+        repo: &str,
+        head: &str,
+    ) -> Result<Option<PullRequest>> {
+        self.with(org, repo, |state| {
+            Ok(state
+                .pull_requests
+                .iter()
+                .find(|p| p.head == head)
+                .map(|p| PullRequest {
+                    number: p.number,
+                    title: p.title.clone(),
+                    author: p.author.clone(),
+                    url: None,
+                }))
+        })
+    }
+
+    async fn create_pull_request(
+        &self,
+        org: &str,
+        repo: &str,
+        payload: &PullRequestPayload,
+    ) -> Result<PullRequest> {
+        self.with(org, repo, |state| {
+            let number = state
+                .pull_requests
+                .iter()
+                .map(|p| p.number)
+                .chain(state.issues.iter().map(|i| i.number))
+                .max()
+                .unwrap_or_default()
+                + 1;
+            state.pull_requests.push(PullRequestDetails {
+                number,
+                title: payload.title.clone(),
+                author: None,
+                base: payload.base.clone(),
+                head: payload.head.clone(),
+                head_sha: String::new(),
+                commits: 0,
+                merged: false,
+                merge_commit_sha: None,
+                labels: vec![],
+            });
+            Ok(PullRequest {
+                number,
+                title: payload.title.clone(),
+                author: None,
+                url: None,
+            })
+        })
+    }
+}
+
+fn parse_due_on(due_on: &Option<String>) -> Result<Option<chrono::DateTime<chrono::Utc>>> {
+    due_on
+        .as_ref()
+        .map(|due_on| {
+            chrono::DateTime::parse_from_rfc3339(due_on)
+                .map(|date| date.to_utc())
+                .map_err(|_| Error::Invalid(format!("due_on {due_on}")))
+        })
+        .transpose()
+}
@@ -0,0 +1,354 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+
+// Label is a label of a repository.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Label {
+    pub name: String,
+    pub color: String,
+    pub description: Option<String>,
+}
+
+// Milestone is a milestone of a repository.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Milestone {
+    pub number: i64,
+    pub title: String,
+    pub description: Option<String>,
+    pub state: Option<String>,
+    pub due_on: Option<DateTime<Utc>>,
+    pub open_issues: Option<i64>,
+}
+
+// MilestonePayload is the body used to create or update a milestone.
+// https://docs.github.com/en/rest/issues/milestones?apiVersion=2022-11-28
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct MilestonePayload {
+    pub title: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due_on: Option<String>,
+}
+
+// Issue is an issue or a pull request, GitHub considers every pull request an
+// issue with the same number.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Issue {
+    pub number: u64,
+    pub title: String,
+    pub state: String,
+    pub milestone: Option<i64>,
+    pub pull_request: bool,
+}
+
+// IssuePayload is the body used to update an issue or pull request, fields
+// left to None are not changed.
+// https://docs.github.com/en/rest/issues/issues?apiVersion=2022-11-28#update-an-issue
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct IssuePayload {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+
+    // State is `open` or `closed`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
+}
+
+// Reference is a git reference and the commit it points to.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Reference {
+    // Name is fully qualified, e.g. `refs/heads/release-0.2`
+    pub name: String,
+    pub sha: String,
+}
+
+// PullRequest is a merged pull request, as listed in the release notes.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PullRequest {
+    pub number: u64,
+    pub title: String,
+    pub author: Option<String>,
+    pub url: Option<String>,
+}
+
+// PullRequestDetails is a single pull request with its branches, as needed to
+// backport it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PullRequestDetails {
+    pub number: u64,
+    pub title: String,
+    pub author: Option<String>,
+    // Base is the branch the PR is merged into, e.g. `main`
+    pub base: String,
+    // Head is the branch the PR is made from
+    pub head: String,
+    // HeadSha is the last commit of the PR
+    pub head_sha: String,
+    // Commits is the number of commits of the PR
+    pub commits: u64,
+    pub merged: bool,
+    // MergeCommitSha is the commit the PR was merged as
+    pub merge_commit_sha: Option<String>,
+    pub labels: Vec<String>,
+}
+
+// PullRequestPayload is the body used to open a pull request.
+// https://docs.github.com/en/rest/pulls/pulls?apiVersion=2022-11-28#create-a-pull-request
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct PullRequestPayload {
+    pub title: String,
+    pub head: String,
+    pub base: String,
+    pub body: String,
+}
+
+// Release is a GitHub release and the tag it is made for.
+// https://docs.github.com/en/rest/releases/releases?apiVersion=2022-11-28
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Release {
+    pub tag_name: String,
+    // Branch or SHA the tag is created from when it does not exist yet
+    pub target_commitish: String,
+    pub name: Option<String>,
+    pub body: Option<String>,
+    pub draft: bool,
+    pub prerelease: bool,
+}
+
+// Comment is a comment on an issue or pull request.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Comment {
+    pub id: u64,
+    pub body: String,
+    pub author: Option<String>,
+}
+
+// CheckRun is the completed result of a check on a commit, GitHub shows it
+// on the pull requests of the commit.
+// https://docs.github.com/en/rest/checks/runs?apiVersion=2022-11-28
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct CheckRun {
+    pub name: String,
+    pub head_sha: String,
+    // Conclusion is `success`, `failure`, `neutral`, ...
+    pub conclusion: String,
+    pub output: CheckRunOutput,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct CheckRunOutput {
+    pub title: String,
+    // Summary is markdown
+    pub summary: String,
+    pub annotations: Vec<Annotation>,
+}
+
+// Annotation points at a line of a file of the commit.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Annotation {
+    pub path: String,
+    pub start_line: u64,
+    pub end_line: u64,
+    // AnnotationLevel is `notice`, `warning` or `failure`
+    pub annotation_level: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    pub message: String,
+}
+
+// GitHub covers the operations our tools make against the GitHub API.
+//
+// It is implemented by `octocrab::Octocrab` (see `client`) and by
+// `fake::FakeGitHub`, which keeps everything in memory for tests.
+#[async_trait]
+pub trait GitHub: Send + Sync {
+    async fn list_labels(&self, org: &str, repo: &str) -> Result<Vec<Label>>;
+
+    async fn create_label(&self, org: &str, repo: &str, label: &Label) -> Result<Label>;
+
+    // update_label patches the label currently called `name`, this includes renaming it
+    async fn update_label(&self, org: &str, repo: &str, name: &str, label: &Label)
+        -> Result<Label>;
+
+    async fn delete_label(&self, org: &str, repo: &str, name: &str) -> Result<()>;
+
+    // list_milestones returns the open milestones
+    async fn list_milestones(&self, org: &str, repo: &str) -> Result<Vec<Milestone>>;
+
+    async fn list_closed_milestones(&self, org: &str, repo: &str) -> Result<Vec<Milestone>>;
+
+    async fn create_milestone(
+        &self,
+        org: &str,
+        repo: &str,
+        milestone: &MilestonePayload,
+    ) -> Result<Milestone>;
+
+    async fn update_milestone(
+        &self,
+        org: &str,
+        repo: &str,
+        number: i64,
+        milestone: &MilestonePayload,
+    ) -> Result<Milestone>;
+
+    // list_milestone_issues returns the open issues and pull requests of a milestone
+    async fn list_milestone_issues(
+        &self,
+        org: &str,
+        repo: &str,
+        milestone: i64,
+    ) -> Result<Vec<Issue>>;
+
+    async fn set_issue_milestone(
+        &self,
+        org: &str,
+        repo: &str,
+        number: u64,
+        milestone: Option<i64>,
+    ) -> Result<()>;
+
+    // get_ref returns the reference, e.g. `heads/main`, None when it does not exist
+    async fn get_ref(&self, org: &str, repo: &str, name: &str) -> Result<Option<Reference>>;
+
+    // resolve_commit returns the commit a branch, tag or SHA points to
+    async fn resolve_commit(
+        &self,
+        org: &str,
+        repo: &str,
+        reference: &str,
+    ) -> Result<Option<String>>;
+
+    async fn create_ref(&self, org: &str, repo: &str, reference: &Reference) -> Result<Reference>;
+
+    // list_tags returns every tag of the repository, e.g. `refs/tags/v0.2.0`
+    async fn list_tags(&self, org: &str, repo: &str) -> Result<Vec<Reference>>;
+
+    // list_branches returns every branch of the repository, e.g. `refs/heads/main`
+    async fn list_branches(&self, org: &str, repo: &str) -> Result<Vec<Reference>>;
+
+    // list_merged_pull_requests returns the pull requests merged as one of the
+    // commits in base...head, in the order of those commits. An empty base
+    // covers every commit of head, e.g. for a first release.
+    async fn list_merged_pull_requests(
+        &self,
+        org: &str,
+        repo: &str,
+        base: &str,
+        head: &str,
+    ) -> Result<Vec<PullRequest>>;
+
+    // get_release returns the release of the tag, None when there is none
+    async fn get_release(&self, org: &str, repo: &str, tag: &str) -> Result<Option<Release>>;
+
+    // get_latest_release returns the latest published release, None before
+    // the first one
+    async fn get_latest_release(&self, org: &str, repo: &str) -> Result<Option<Release>>;
+
+    // generate_release_notes returns the notes GitHub writes for a release,
+    // they end with the new contributors and a link to the full changelog
+    async fn generate_release_notes(
+        &self,
+        org: &str,
+        repo: &str,
+        tag: &str,
+        target: &str,
+        previous: Option<&str>,
+    ) -> Result<String>;
+
+    async fn create_release(&self, org: &str, repo: &str, release: &Release) -> Result<Release>;
+
+    async fn list_comments(&self, org: &str, repo: &str, number: u64) -> Result<Vec<Comment>>;
+
+    async fn create_comment(
+        &self,
+        org: &str,
+        repo: &str,
+        number: u64,
+        body: &str,
+    ) -> Result<Comment>;
+
+    async fn update_comment(&self, org: &str, repo: &str, id: u64, body: &str) -> Result<Comment>;
+
+    async fn delete_comment(&self, org: &str, repo: &str, id: u64) -> Result<()>;
+
+    async fn create_check_run(&self, org: &str, repo: &str, check_run: &CheckRun) -> Result<()>;
+
+    // list_issue_labels returns the names of the labels of an issue or PR
+    async fn list_issue_labels(&self, org: &str, repo: &str, number: u64) -> Result<Vec<String>>;
+
+    async fn add_issue_labels(
+        &self,
+        org: &str,
+        repo: &str,
+        number: u64,
+        labels: &[String],
+    ) -> Result<()>;
+
+    async fn remove_issue_label(
+        &self,
+        org: &str,
+        repo: &str,
+        number: u64,
+        name: &str,
+    ) -> Result<()>;
+
+    async fn update_issue(
+        &self,
+        org: &str,
+        repo: &str,
+        number: u64,
+        issue: &IssuePayload,
+    ) -> Result<()>;
+
+    async fn add_assignees(
+        &self,
+        org: &str,
+        repo: &str,
+        number: u64,
+        assignees: &[String],
+    ) -> Result<()>;
+
+    async fn remove_assignees(
+        &self,
+        org: &str,
+        repo: &str,
+        number: u64,
+        assignees: &[String],
+    ) -> Result<()>;
+
+    // get_permission returns the role of a user in the repository: `admin`,
+    // `maintain`, `write`, `triage`, `read` or `none`
+    async fn get_permission(&self, org: &str, repo: &str, user: &str) -> Result<String>;
+
+    async fn get_pull_request(
+        &self,
+        org: &str,
+        repo: &str,
+        number: u64,
+    ) -> Result<PullRequestDetails>;
+
+    // find_pull_request returns the PR made from the branch, open or not
+    async fn find_pull_request(
+        &self,
+        org: &str,
+        repo: &str,
+        head: &str,
+    ) -> Result<Option<PullRequest>>;
+
+    async fn create_pull_request(
+        &self,
+        org: &str,
+        repo: &str,
+        pull_request: &PullRequestPayload,
+    ) -> Result<PullRequest>;
+}
@@ -2,3 +2,5 @@ pub mod client;
 pub mod commands;
 pub mod context;
 pub mod error;
+pub mod fake;
+pub mod github;
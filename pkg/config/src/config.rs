@@ -1,11 +1,587 @@
-use std::fs;
+use std::{collections::HashMap, fmt, fs, str::FromStr};
 
-use crate::{error::Result, types::Configuration};
+use chrono::{DateTime, Days, NaiveDate};
+
+use crate::{
+    error::{Error, Result},
+    types::{
+        Commands, Configuration, Due, Label, LabelTemplate, Milestone, Overrides, PRKind,
+        Permission, Prune, Repo, State,
+    },
+    validate::validate,
+};
 
 impl Configuration {
     pub fn from_path(path: &str) -> Result<Self> {
         let data = fs::read_to_string(path)?;
+
+        let problems = validate(&data);
+        if !problems.is_empty() {
+            return Err(Error::Invalid {
+                path: path.to_string(),
+                problems,
+            });
+        }
+
         let data: Configuration = serde_yml::from_str(&data)?;
         Ok(data)
     }
+
+    // labels_for returns the labels the repository should have.
+    pub fn labels_for(&self, repo: &Repo) -> Vec<Label> {
+        resolve(&self.labels, &self.groups.labels, &repo.labels, |l| &l.name)
+    }
+
+    // milestones_for returns the milestones the repository should have.
+    pub fn milestones_for(&self, repo: &Repo) -> Vec<Milestone> {
+        resolve(
+            &self.milestones,
+            &self.groups.milestones,
+            &repo.milestones,
+            |m| &m.title,
+        )
+    }
+
+    pub fn find_repo(&self, org: &str, repo: &str) -> Option<&Repo> {
+        self.repos
+            .iter()
+            .find(|r| r.org.eq_ignore_ascii_case(org) && r.repo.eq_ignore_ascii_case(repo))
+    }
+}
+
+impl Prune {
+    // is_ignored reports whether the label matches one of the ignore patterns,
+    // label names are case-insensitive on GitHub.
+    pub fn is_ignored(&self, name: &str) -> bool {
+        self.ignore
+            .iter()
+            .any(|pattern| wildcard_match(&pattern.to_lowercase(), &name.to_lowercase()))
+    }
+}
+
+impl Commands {
+    // permission returns who may run the command, `name` is without the slash
+    pub fn permission(&self, name: &str) -> Permission {
+        let name = name.to_lowercase();
+        self.permissions
+            .get(&name)
+            .or_else(|| {
+                name.strip_prefix("remove-")
+                    .and_then(|prefix| self.permissions.get(prefix))
+            })
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+// BRANCH is replaced by the name of the branch in label templates
+pub const BRANCH: &str = "{branch}";
+
+impl LabelTemplate {
+    // matches tells whether the branch gets a label
+    pub fn matches(&self, branch: &str) -> bool {
+        wildcard_match(&self.branches, branch)
+    }
+
+    // expand returns the label of the branch
+    pub fn expand(&self, branch: &str) -> Label {
+        Label {
+            name: self.name.replace(BRANCH, branch),
+            color: self.color.clone(),
+            description: self
+                .description
+                .as_ref()
+                .map(|description| description.replace(BRANCH, branch)),
+            previously: vec![],
+        }
+    }
+
+    // branch_of returns the branch a label was generated for, if it was
+    // generated by this template
+    pub fn branch_of(&self, name: &str) -> Option<String> {
+        let (prefix, suffix) = self.name.split_once(BRANCH)?;
+        let (head, rest) = name.split_at_checked(prefix.len())?;
+        let (branch, tail) = rest.split_at_checked(rest.len().checked_sub(suffix.len())?)?;
+
+        // Label names are case-insensitive
+        if !head.eq_ignore_ascii_case(prefix) || !tail.eq_ignore_ascii_case(suffix) {
+            return None;
+        }
+        Some(branch.to_string()).filter(|branch| !branch.is_empty() && self.matches(branch))
+    }
+}
+
+impl Permission {
+    // allows tells whether a user with this role in the repository, see
+    // `GitHub::get_permission`, may run the command
+    pub fn allows(&self, role: &str, author: bool) -> bool {
+        let collaborator = matches!(role, "admin" | "maintain" | "write");
+        match self {
+            Permission::Anyone => true,
+            Permission::Author => author || collaborator,
+            Permission::Collaborator => collaborator,
+            Permission::Maintainer => matches!(role, "admin" | "maintain"),
+        }
+    }
+}
+
+impl State {
+    // as_str is the value used by the GitHub API
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            State::Open => "open",
+            State::Closed => "closed",
+        }
+    }
+}
+
+impl PRKind {
+    // as_str is the name used in config.yaml
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PRKind::Feature => "feature",
+            PRKind::Bug => "bug",
+            PRKind::Docs => "docs",
+            PRKind::Infra => "infra",
+            PRKind::Breaking => "breaking",
+            PRKind::NoNote => "no-note",
+        }
+    }
+}
+
+impl fmt::Display for PRKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for State {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_str() {
+            "open" => Ok(State::Open),
+            "closed" => Ok(State::Closed),
+            _ => Err(Error::InvalidState(value.to_string())),
+        }
+    }
+}
+
+impl TryFrom<String> for State {
+    type Error = Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        value.parse()
+    }
+}
+
+impl fmt::Display for State {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl From<State> for String {
+    fn from(state: State) -> Self {
+        state.as_str().to_string()
+    }
+}
+
+// MAX_DAYS bounds relative due dates, a century is far more than a
+// milestone ever needs and keeps the resolved date in range.
+const MAX_DAYS: u32 = 36_500;
+
+impl Due {
+    // resolve returns the date, `base` is what relative dates count from. The
+    // parser bounds relative dates, they only overflow past year 262000.
+    pub fn resolve(&self, base: NaiveDate) -> NaiveDate {
+        match self {
+            Due::Date(date) => *date,
+            Due::After(days) => base
+                .checked_add_days(Days::new(*days as u64))
+                .unwrap_or(NaiveDate::MAX),
+        }
+    }
+}
+
+impl FromStr for Due {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        let invalid = || Error::InvalidDue(value.to_string());
+
+        if let Some(relative) = value.strip_prefix('+') {
+            let (count, factor) = match (relative.strip_suffix('d'), relative.strip_suffix('w')) {
+                (Some(count), _) => (count, 1),
+                (_, Some(count)) => (count, 7),
+                _ => return Err(invalid()),
+            };
+            let days = count
+                .parse::<u32>()
+                .ok()
+                .and_then(|count| count.checked_mul(factor))
+                .filter(|days| *days <= MAX_DAYS)
+                .ok_or_else(invalid)?;
+            return Ok(Due::After(days));
+        }
+
+        if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+            return Ok(Due::Date(date));
+        }
+        DateTime::parse_from_rfc3339(value)
+            .map(|datetime| Due::Date(datetime.date_naive()))
+            .map_err(|_| invalid())
+    }
+}
+
+impl TryFrom<String> for Due {
+    type Error = Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        value.parse()
+    }
+}
+
+impl fmt::Display for Due {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Due::Date(date) => write!(f, "{}", date.format("%Y-%m-%d")),
+            Due::After(days) if days % 7 == 0 => write!(f, "+{}w", days / 7),
+            Due::After(days) => write!(f, "+{}d", days),
+        }
+    }
+}
+
+impl From<Due> for String {
+    fn from(due: Due) -> Self {
+        due.to_string()
+    }
+}
+
+// resolve applies the overrides of a repository to the shared list, entries
+// are identified by their case-insensitive name.
+fn resolve<T: Clone>(
+    shared: &[T],
+    groups: &HashMap<String, Vec<T>>,
+    overrides: &Overrides<T>,
+    name: impl Fn(&T) -> &str,
+) -> Vec<T> {
+    let included = overrides
+        .include
+        .iter()
+        .filter_map(|group| groups.get(group))
+        .flatten();
+
+    let mut resolved: Vec<T> = vec![];
+    let mut upsert = |item: &T| {
+        let key = name(item).to_lowercase();
+        match resolved.iter().position(|r| name(r).to_lowercase() == key) {
+            Some(index) => resolved[index] = item.clone(),
+            None => resolved.push(item.clone()),
+        }
+    };
+
+    for item in shared {
+        let key = name(item).to_lowercase();
+        if !overrides
+            .exclude
+            .iter()
+            .any(|pattern| wildcard_match(&pattern.to_lowercase(), &key))
+        {
+            upsert(item);
+        }
+    }
+    for item in included.chain(&overrides.extra) {
+        upsert(item);
+    }
+
+    resolved
+}
+
+// wildcard_match matches `value` against `pattern` where `*` matches any
+// sequence of characters.
+fn wildcard_match(pattern: &str, value: &str) -> bool {
+    let mut parts = pattern.split('*');
+
+    // There is always a first part, it must be a prefix of the value.
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = value.strip_prefix(first) else {
+        return false;
+    };
+
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // No wildcard at all.
+        return rest.is_empty();
+    };
+
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+
+    rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use crate::types::{Commands, Configuration, Due, LabelTemplate, Permission, Prune, State};
+
+    const CONFIG: &str = r#"
+repos:
+  - org: org
+    repo: server
+  - org: org
+    repo: operator
+    labels:
+      include: [operator]
+      exclude: ["cherry-pick/*"]
+      extra:
+        - name: kind/bug
+          color: ff0000
+    milestones:
+      include: [operator]
+      exclude: ["*"]
+labels:
+  - name: kind/bug
+    color: e11d21
+  - name: cherry-pick/release-0.2
+    color: fef2a0
+milestones:
+  - title: Trustify 2.0.0
+    description: The v0.2.0 release of Trustify
+groups:
+  labels:
+    operator:
+      - name: area/operator
+        color: 0e8a16
+  milestones:
+    operator:
+      - title: Operator 0.1.0
+        description: The first release of the operator
+"#;
+
+    #[test]
+    fn resolve() {
+        let configuration: Configuration = serde_yml::from_str(CONFIG).unwrap();
+        let labels = |repo: &str| -> Vec<(String, String)> {
+            let repo = configuration.find_repo("org", repo).unwrap();
+            configuration
+                .labels_for(repo)
+                .into_iter()
+                .map(|l| (l.name, l.color))
+                .collect()
+        };
+        let milestones = |repo: &str| -> Vec<String> {
+            let repo = configuration.find_repo("org", repo).unwrap();
+            configuration
+                .milestones_for(repo)
+                .into_iter()
+                .map(|m| m.title)
+                .collect()
+        };
+
+        assert_eq!(
+            vec![
+                ("kind/bug".to_string(), "e11d21".to_string()),
+                ("cherry-pick/release-0.2".to_string(), "fef2a0".to_string()),
+            ],
+            labels("server")
+        );
+        assert_eq!(vec!["Trustify 2.0.0"], milestones("server"));
+
+        assert_eq!(
+            vec![
+                ("kind/bug".to_string(), "ff0000".to_string()),
+                ("area/operator".to_string(), "0e8a16".to_string()),
+            ],
+            labels("operator")
+        );
+        assert_eq!(vec!["Operator 0.1.0"], milestones("operator"));
+    }
+
+    #[test]
+    fn prune_ignore() {
+        let prune = Prune {
+            ignore: vec![
+                "dependencies".to_string(),
+                "cherry-pick/*".to_string(),
+                "*-wanted".to_string(),
+                "lifecycle/*/stale".to_string(),
+            ],
+        };
+
+        assert!(prune.is_ignored("dependencies"));
+        assert!(prune.is_ignored("Dependencies"));
+        assert!(prune.is_ignored("cherry-pick/release-0.2"));
+        assert!(prune.is_ignored("help-wanted"));
+        assert!(prune.is_ignored("lifecycle/issue/stale"));
+
+        assert!(!prune.is_ignored("dependencies-bot"));
+        assert!(!prune.is_ignored("cherry-pick"));
+        assert!(!prune.is_ignored("wanted-help"));
+        assert!(!prune.is_ignored("lifecycle/stale/rotten"));
+        assert!(!prune.is_ignored("enhancement"));
+    }
+
+    #[test]
+    fn command_permission() {
+        let commands = Commands {
+            labels: vec!["kind".to_string()],
+            permissions: [
+                ("kind".to_string(), Permission::Anyone),
+                ("remove-kind".to_string(), Permission::Author),
+                ("priority".to_string(), Permission::Maintainer),
+            ]
+            .into(),
+        };
+
+        assert_eq!(Permission::Anyone, commands.permission("kind"));
+        assert_eq!(Permission::Author, commands.permission("remove-kind"));
+        assert_eq!(
+            Permission::Maintainer,
+            commands.permission("Remove-Priority")
+        );
+        assert_eq!(Permission::Collaborator, commands.permission("close"));
+
+        assert!(Permission::Author.allows("read", true));
+        assert!(Permission::Author.allows("write", false));
+        assert!(!Permission::Collaborator.allows("triage", false));
+        assert!(!Permission::Maintainer.allows("write", false));
+        assert!(Permission::Maintainer.allows("admin", false));
+    }
+
+    #[test]
+    fn label_template() {
+        let template = LabelTemplate {
+            name: "cherry-pick/{branch}".to_string(),
+            color: "fef2a0".to_string(),
+            description: Some("Cherry-pick to the {branch} branch.".to_string()),
+            branches: "release-*".to_string(),
+        };
+
+        let label = template.expand("release-0.2");
+        assert_eq!("cherry-pick/release-0.2", label.name);
+        assert_eq!(
+            Some("Cherry-pick to the release-0.2 branch.".to_string()),
+            label.description
+        );
+
+        assert!(template.matches("release-0.2"));
+        assert!(!template.matches("main"));
+
+        let branch_of = |name: &str| template.branch_of(name);
+        assert_eq!(
+            Some("release-0.2".to_string()),
+            branch_of("cherry-pick/release-0.2")
+        );
+        assert_eq!(
+            Some("release-0.3".to_string()),
+            branch_of("Cherry-Pick/release-0.3")
+        );
+        assert_eq!(None, branch_of("cherry-pick/release/0.2.z"));
+        assert_eq!(None, branch_of("cherry-pick/"));
+        assert_eq!(None, branch_of("kind/bug"));
+    }
+
+    #[test]
+    fn due() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+
+        struct TestCase {
+            value: &'static str,
+            expected: Option<Due>,
+        }
+
+        let test_cases = vec![
+            TestCase {
+                value: "2026-11-30",
+                expected: Some(Due::Date(date(2026, 11, 30))),
+            },
+            TestCase {
+                value: "2026-11-30T23:30:00-05:00",
+                expected: Some(Due::Date(date(2026, 11, 30))),
+            },
+            TestCase {
+                value: "2026-11-30T08:00:00Z",
+                expected: Some(Due::Date(date(2026, 11, 30))),
+            },
+            TestCase {
+                value: "+6w",
+                expected: Some(Due::After(42)),
+            },
+            TestCase {
+                value: "+10d",
+                expected: Some(Due::After(10)),
+            },
+            TestCase {
+                value: "2026-11-31",
+                expected: None,
+            },
+            TestCase {
+                value: "+6m",
+                expected: None,
+            },
+            TestCase {
+                value: "+w",
+                expected: None,
+            },
+            TestCase {
+                value: "next week",
+                expected: None,
+            },
+            TestCase {
+                value: "+1日",
+                expected: None,
+            },
+            TestCase {
+                value: "+36500d",
+                expected: Some(Due::After(36_500)),
+            },
+            TestCase {
+                value: "+200000000d",
+                expected: None,
+            },
+            TestCase {
+                value: "+5215w",
+                expected: None,
+            },
+        ];
+
+        for test_case in test_cases {
+            let due: Option<Due> = test_case.value.parse().ok();
+            assert_eq!(test_case.expected, due, "{}", test_case.value);
+
+            if let Some(due) = due {
+                let round_trip: Due = due.to_string().parse().unwrap();
+                assert_eq!(due, round_trip);
+            }
+        }
+
+        assert_eq!(
+            date(2026, 12, 14),
+            Due::After(14).resolve(date(2026, 11, 30))
+        );
+        assert_eq!(
+            date(2026, 11, 30),
+            Due::Date(date(2026, 11, 30)).resolve(date(2025, 1, 1))
+        );
+        assert_eq!(NaiveDate::MAX, Due::After(14).resolve(NaiveDate::MAX));
+    }
+
+    #[test]
+    fn state() {
+        assert_eq!(State::Open, "open".parse().unwrap());
+        assert_eq!(State::Open, "Open".parse().unwrap());
+        assert_eq!(State::Closed, "CLOSED".parse().unwrap());
+        assert_eq!(
+            "invalid state \"opne\", expected open or closed",
+            "opne".parse::<State>().unwrap_err().to_string()
+        );
+        assert_eq!("closed", String::from(State::Closed));
+    }
 }
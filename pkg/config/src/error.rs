@@ -2,6 +2,21 @@ pub type Result<T> = std::result::Result<T, Error>;
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
+    #[error("invalid state {0:?}, expected open or closed")]
+    InvalidState(String),
+
+    #[error("invalid due date {0:?}, expected YYYY-MM-DD, RFC 3339 or +N[dw]")]
+    InvalidDue(String),
+
+    #[error(
+        "invalid configuration{}",
+        .problems.iter().map(|p| format!("\n{}:{}", .path, p)).collect::<String>()
+    )]
+    Invalid {
+        path: String,
+        problems: Vec<crate::validate::Problem>,
+    },
+
     #[error(transparent)]
     Serde(#[from] serde_yml::Error),
 
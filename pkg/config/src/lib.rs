@@ -1,3 +1,4 @@
 pub mod config;
 pub mod error;
 pub mod types;
+pub mod validate;
@@ -1,20 +1,258 @@
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use pr::{
+    convention::{Convention, ConventionalCommits, Emoji},
+    prefix::PRType,
+};
 use serde::{Deserialize, Serialize};
 
 // Configuration is a representation of the repositories we will manage
 // + their Labels
 // + their Milestons
+//
+// `labels` and `milestones` are shared by every repository, use
+// `Configuration::labels_for` and `Configuration::milestones_for` to get the
+// effective ones once groups and overrides are applied.
 #[derive(Deserialize)]
 pub struct Configuration {
     pub repos: Vec<Repo>,
     pub labels: Vec<Label>,
     pub milestones: Vec<Milestone>,
+
+    // LabelTemplates generate labels from the branches of every repository
+    #[serde(default)]
+    pub label_templates: Vec<LabelTemplate>,
+
+    // Groups are named sets of labels and milestones repos can include
+    #[serde(default)]
+    pub groups: Groups,
+
+    // Prune controls which unmanaged labels are kept when pruning
+    #[serde(default)]
+    pub prune: Prune,
+
+    // Verify holds the rules verify-pr enforces besides the title convention
+    #[serde(default)]
+    pub verify: Verify,
+
+    // Triage holds the placeholder labels and comments of issues and PRs
+    #[serde(default)]
+    pub triage: Triage,
+
+    // Commands holds the slash commands accepted in issue and PR comments
+    #[serde(default)]
+    pub commands: Commands,
 }
 
 // Repo represents the "coordinates" to a repository
-#[derive(Deserialize)]
+#[derive(Deserialize, Default)]
 pub struct Repo {
     pub org: String,
     pub repo: String,
+
+    // Convention is how PR titles in the repository express their type
+    #[serde(default)]
+    pub convention: TitleConvention,
+
+    // Labels tailors the shared labels to this repository
+    #[serde(default)]
+    pub labels: Overrides<Label>,
+
+    // Milestones tailors the shared milestones to this repository
+    #[serde(default)]
+    pub milestones: Overrides<Milestone>,
+}
+
+// Groups holds named sets, e.g. the labels only the operator repos need.
+#[derive(Deserialize, Default)]
+pub struct Groups {
+    #[serde(default)]
+    pub labels: HashMap<String, Vec<Label>>,
+
+    #[serde(default)]
+    pub milestones: HashMap<String, Vec<Milestone>>,
+}
+
+// Overrides turn the shared list into the one of a repository:
+// shared - excluded + included groups + extra.
+#[derive(Deserialize)]
+#[serde(bound(deserialize = "T: Deserialize<'de>"))]
+pub struct Overrides<T> {
+    // Include is a list of group names
+    #[serde(default)]
+    pub include: Vec<String>,
+
+    // Exclude is a list of shared names or titles to leave out, `*` matches
+    // any sequence of characters
+    #[serde(default)]
+    pub exclude: Vec<String>,
+
+    // Extra entries only exist in this repository, they replace shared ones
+    // with the same name
+    #[serde(default)]
+    pub extra: Vec<T>,
+}
+
+impl<T> Default for Overrides<T> {
+    fn default() -> Self {
+        Overrides {
+            include: vec![],
+            exclude: vec![],
+            extra: vec![],
+        }
+    }
+}
+
+// TitleConvention is the PR title convention a repository follows
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum TitleConvention {
+    // `:sparkles: Add new feature`, see VERSIONING.md
+    #[default]
+    Emoji,
+
+    // `feat: Add new feature`, see https://www.conventionalcommits.org
+    ConventionalCommits,
+}
+
+impl TitleConvention {
+    // convention returns the parser of the titles for the convention
+    pub fn convention(&self) -> Box<dyn Convention> {
+        match self {
+            TitleConvention::Emoji => Box::new(Emoji),
+            TitleConvention::ConventionalCommits => Box::new(ConventionalCommits),
+        }
+    }
+}
+
+// PRKind is the type a PR title expresses, independent of the convention.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub enum PRKind {
+    Feature,
+    Bug,
+    Docs,
+    Infra,
+    Breaking,
+    NoNote,
+}
+
+impl PRKind {
+    // pr_type returns the type of the kind with the title
+    pub fn pr_type(self, title: String) -> PRType {
+        match self {
+            PRKind::Feature => PRType::Feature(title),
+            PRKind::Bug => PRType::BugFix(title),
+            PRKind::Docs => PRType::Docs(title),
+            PRKind::Infra => PRType::Infra(title),
+            PRKind::Breaking => PRType::Breaking(title),
+            PRKind::NoNote => PRType::NoNote(title),
+        }
+    }
+}
+
+impl From<&PRType> for PRKind {
+    fn from(pr_type: &PRType) -> Self {
+        match pr_type {
+            PRType::Feature(_) => PRKind::Feature,
+            PRType::BugFix(_) => PRKind::Bug,
+            PRType::Docs(_) => PRKind::Docs,
+            PRType::Infra(_) => PRKind::Infra,
+            PRType::Breaking(_) => PRKind::Breaking,
+            PRType::NoNote(_) => PRKind::NoNote,
+        }
+    }
+}
+
+// Verify holds the PR rules, every rule is off unless configured.
+#[derive(Deserialize, Default)]
+pub struct Verify {
+    // KindLabels is the label each type must carry once a PR has any
+    // `kind/*` label, e.g. `bug: kind/bug`
+    #[serde(default)]
+    pub kind_labels: HashMap<PRKind, String>,
+
+    // LinkedIssue lists the types that must link an issue, e.g. `Fixes #123`
+    #[serde(default)]
+    pub linked_issue: Vec<PRKind>,
+
+    // ReleaseNote lists the types that need a non-empty release-note block
+    #[serde(default)]
+    pub release_note: Vec<PRKind>,
+
+    // ReleaseBranches rejects features and breaking changes against
+    // `release-X.Y` branches, backports must be tagged with their branch and
+    // reference the original PR
+    #[serde(default)]
+    pub release_branches: bool,
+}
+
+// Triage holds the rules the triage tool applies to every issue and PR.
+#[derive(Deserialize, Default)]
+pub struct Triage {
+    #[serde(default)]
+    pub rules: Vec<TriageRule>,
+}
+
+// TriageRule keeps a placeholder label and a comment on an issue depending on
+// the labels starting with `prefix`, e.g. `needs-kind` until a `kind/*` label
+// is added.
+#[derive(Deserialize, Clone)]
+pub struct TriageRule {
+    // Prefix of the labels the rule looks for, a full name matches too
+    pub prefix: String,
+
+    // Placeholder is the label kept while the rule applies
+    pub placeholder: Option<String>,
+
+    // Comment is the text kept as a bot comment while the rule applies
+    pub comment: Option<String>,
+
+    // When the rule applies
+    #[serde(default)]
+    pub when: When,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum When {
+    // While no label starts with the prefix
+    #[default]
+    Missing,
+    // While a label starts with the prefix
+    Present,
+}
+
+// Commands configures the slash commands, e.g. `/kind bug` adds the
+// `kind/bug` label.
+#[derive(Deserialize, Default)]
+pub struct Commands {
+    // Labels are the prefixes of the label commands, `/<prefix> <name>` adds
+    // `<prefix>/<name>` and `/remove-<prefix> <name>` removes it
+    #[serde(default)]
+    pub labels: Vec<String>,
+
+    // Permissions is who may run a command, by name without the slash, e.g.
+    // `kind` or `close`. `remove-<prefix>` falls back to `<prefix>`, commands
+    // without an entry need a collaborator
+    #[serde(default)]
+    pub permissions: HashMap<String, Permission>,
+}
+
+// Permission is who may run a command, every level includes the next ones.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Permission {
+    // Anyone who can comment
+    Anyone,
+    // The author of the issue or PR
+    Author,
+    // Users with write access to the repository
+    #[default]
+    Collaborator,
+    // Users with the maintain or admin role
+    Maintainer,
 }
 
 // Label holds declarative data about the label.
@@ -28,6 +266,36 @@ pub struct Label {
 
     // Description is brief text explaining its meaning, who can apply it
     pub description: Option<String>,
+
+    // Previously is a list of former names, an existing label with one of
+    // these names is renamed instead of creating a new label
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub previously: Vec<String>,
+}
+
+// LabelTemplate generates a label per branch of a repository matching
+// `branches`, e.g. `cherry-pick/{branch}` for every `release-*` branch. The
+// labels of branches that are gone are deleted.
+#[derive(Deserialize, Clone)]
+pub struct LabelTemplate {
+    // Name contains `{branch}`, replaced by the name of the branch
+    pub name: String,
+
+    pub color: String,
+
+    // Description may contain `{branch}` too
+    pub description: Option<String>,
+
+    // Branches is a branch name, `*` matches any sequence of characters
+    pub branches: String,
+}
+
+// Prune holds the labels that are not in `labels` but must not be deleted.
+#[derive(Deserialize, Default)]
+pub struct Prune {
+    // Ignore is a list of label names, `*` matches any sequence of characters
+    #[serde(default)]
+    pub ignore: Vec<String>,
 }
 
 // Milestone holds declarative data about the milestone.
@@ -35,9 +303,48 @@ pub struct Label {
 pub struct Milestone {
     pub title: String,
     pub description: Option<String>,
-    pub state: Option<String>,
-    pub due: Option<String>,
+    pub state: Option<State>,
+    pub due: Option<Due>,
     pub replaces: Option<String>,
+
+    // OnMissing is what to do in repositories without the replaced milestone
+    #[serde(default)]
+    pub on_missing: OnMissing,
+}
+
+// State of a milestone, parsed case-insensitively.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(try_from = "String", into = "String")]
+pub enum State {
+    Open,
+    Closed,
+}
+
+// Due is when a milestone is due, GitHub only keeps the date.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(try_from = "String", into = "String")]
+pub enum Due {
+    // `2026-11-30`, or RFC 3339 `2026-11-30T17:00:00+01:00` which keeps the
+    // date in its own timezone
+    Date(NaiveDate),
+
+    // `+6w` or `+10d`, a number of days after the due date of the replaced
+    // milestone, or after the day the milestone is created
+    After(u32),
+}
+
+// OnMissing handles a `replaces` naming a milestone that never existed in a
+// repository, e.g. a repository added after the previous release.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum OnMissing {
+    // Manage the milestone, nothing to move
+    Skip,
+    // Same as skip, plus a warning in the plan
+    #[default]
+    Warn,
+    // Stop before making any change
+    Fail,
 }
 
 impl std::fmt::Display for Milestone {
@@ -0,0 +1,598 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use serde::de::DeserializeOwned;
+use serde_yml::{
+    de::{Event, Progress},
+    libyml::error::Mark,
+    loader::Loader,
+    Mapping, Value,
+};
+
+use crate::{
+    config::BRANCH,
+    types::{Configuration, Due, Label, Milestone, State},
+};
+
+// Problem is something wrong in config.yaml, found before any API call.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Problem {
+    // Path of the offending value, e.g. `labels[3].color`
+    pub path: String,
+
+    // Line and column of the value, 1-based
+    pub line: usize,
+    pub column: usize,
+
+    pub message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: ", self.line, self.column)?;
+        if !self.path.is_empty() {
+            write!(f, "{}: ", self.path)?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+// validate parses the configuration and returns every problem in it, an
+// empty list means the configuration is valid.
+pub fn validate(source: &str) -> Vec<Problem> {
+    let mut value: Value = match serde_yml::from_str(source) {
+        Ok(value) => value,
+        Err(error) => return vec![parse_problem(&error)],
+    };
+
+    let mut checks = Checks::default();
+    checks.typed_fields(&mut value);
+
+    let configuration: Configuration = match serde_yml::from_value(value) {
+        Ok(configuration) => configuration,
+        Err(error) => {
+            // Errors from a value have no location, parsing the source again
+            // finds the same error with one when nothing was dropped
+            let error = match serde_yml::from_str::<Configuration>(source) {
+                Err(located) if checks.0.is_empty() => located,
+                _ => error,
+            };
+            let mut problems = checks.into_problems(source);
+            problems.push(parse_problem(&error));
+            return problems;
+        }
+    };
+
+    checks.repos(&configuration);
+    checks.labels("labels", &configuration.labels);
+    checks.milestones("milestones", &configuration.milestones);
+    checks.label_templates(&configuration);
+    for (name, labels) in &configuration.groups.labels {
+        checks.labels(&format!("groups.labels.{name}"), labels);
+    }
+    for (name, milestones) in &configuration.groups.milestones {
+        checks.milestones(&format!("groups.milestones.{name}"), milestones);
+    }
+    checks.verify(&configuration);
+    checks.triage(&configuration);
+    checks.commands(&configuration);
+
+    checks.into_problems(source)
+}
+
+fn parse_problem(error: &serde_yml::Error) -> Problem {
+    let (line, column) = error
+        .location()
+        .map(|location| (location.line(), location.column()))
+        .unwrap_or((1, 1));
+    Problem {
+        path: String::new(),
+        line,
+        column,
+        message: error.to_string(),
+    }
+}
+
+// Checks collects (path, message) pairs, locations are resolved at the end.
+#[derive(Default)]
+struct Checks(Vec<(String, String)>);
+
+impl Checks {
+    fn push(&mut self, path: String, message: String) {
+        self.0.push((path, message));
+    }
+
+    fn into_problems(self, source: &str) -> Vec<Problem> {
+        let locations = locations(source);
+        let mut problems: Vec<Problem> = self
+            .0
+            .into_iter()
+            .map(|(path, message)| {
+                let (line, column) = locate(&locations, &path);
+                Problem {
+                    path,
+                    line,
+                    column,
+                    message,
+                }
+            })
+            .collect();
+        problems.sort_by_key(|p| (p.line, p.column));
+        problems
+    }
+
+    // typed_fields checks the milestone fields with their own syntax, invalid
+    // ones are dropped so the rest of the configuration is still checked.
+    fn typed_fields(&mut self, value: &mut Value) {
+        self.milestone_fields("milestones", value.get_mut("milestones"));
+
+        if let Some(Value::Mapping(groups)) = value
+            .get_mut("groups")
+            .and_then(|groups| groups.get_mut("milestones"))
+        {
+            for (name, milestones) in groups.iter_mut() {
+                let path = format!("groups.milestones.{}", name.as_str().unwrap_or_default());
+                self.milestone_fields(&path, Some(milestones));
+            }
+        }
+
+        if let Some(Value::Sequence(repos)) = value.get_mut("repos") {
+            for (i, repo) in repos.iter_mut().enumerate() {
+                let extra = repo
+                    .get_mut("milestones")
+                    .and_then(|milestones| milestones.get_mut("extra"));
+                self.milestone_fields(&format!("repos[{i}].milestones.extra"), extra);
+            }
+        }
+    }
+
+    fn milestone_fields(&mut self, path: &str, milestones: Option<&mut Value>) {
+        let Some(Value::Sequence(milestones)) = milestones else {
+            return;
+        };
+
+        for (i, milestone) in milestones.iter_mut().enumerate() {
+            let Value::Mapping(milestone) = milestone else {
+                continue;
+            };
+            self.typed_field::<State>(format!("{path}[{i}]"), milestone, "state");
+            self.typed_field::<Due>(format!("{path}[{i}]"), milestone, "due");
+        }
+    }
+
+    fn typed_field<T: DeserializeOwned>(&mut self, path: String, mapping: &mut Mapping, key: &str) {
+        let Some(value) = mapping.get(key) else {
+            return;
+        };
+        if value.is_null() {
+            return;
+        }
+
+        if let Err(error) = serde_yml::from_value::<T>(value.clone()) {
+            self.push(format!("{path}.{key}"), error.to_string());
+            mapping.remove(key);
+        }
+    }
+
+    fn repos(&mut self, configuration: &Configuration) {
+        let mut seen: HashSet<String> = HashSet::new();
+        for (i, repo) in configuration.repos.iter().enumerate() {
+            let path = format!("repos[{i}]");
+            let name = format!("{}/{}", repo.org, repo.repo);
+            if !seen.insert(name.to_lowercase()) {
+                self.push(format!("{path}.repo"), format!("duplicate repo {name}"));
+            }
+
+            for (j, group) in repo.labels.include.iter().enumerate() {
+                if !configuration.groups.labels.contains_key(group) {
+                    self.push(
+                        format!("{path}.labels.include[{j}]"),
+                        format!("unknown labels group {group:?}"),
+                    );
+                }
+            }
+            for (j, group) in repo.milestones.include.iter().enumerate() {
+                if !configuration.groups.milestones.contains_key(group) {
+                    self.push(
+                        format!("{path}.milestones.include[{j}]"),
+                        format!("unknown milestones group {group:?}"),
+                    );
+                }
+            }
+
+            self.labels(&format!("{path}.labels.extra"), &repo.labels.extra);
+            self.milestones(&format!("{path}.milestones.extra"), &repo.milestones.extra);
+        }
+    }
+
+    fn label_templates(&mut self, configuration: &Configuration) {
+        for (i, template) in configuration.label_templates.iter().enumerate() {
+            let path = format!("label_templates[{i}]");
+
+            if template.name.matches(BRANCH).count() != 1 {
+                self.push(
+                    format!("{path}.name"),
+                    format!("name {:?} must contain {BRANCH} once", template.name),
+                );
+            }
+            if template.color.len() != 6 || !template.color.chars().all(|c| c.is_ascii_hexdigit()) {
+                self.push(
+                    format!("{path}.color"),
+                    format!("invalid color {:?}, expected rrggbb", template.color),
+                );
+            }
+            if template.branches.trim().is_empty() {
+                self.push(format!("{path}.branches"), "branches is empty".to_string());
+            }
+        }
+    }
+
+    fn verify(&mut self, configuration: &Configuration) {
+        // Any label defined in the configuration will do, the rule only
+        // applies where the PR carries it
+        let names = label_names(configuration);
+
+        let mut kind_labels: Vec<_> = configuration.verify.kind_labels.iter().collect();
+        kind_labels.sort_by_key(|(_, label)| label.as_str());
+        for (kind, label) in kind_labels {
+            if !names.contains(&label.to_lowercase()) {
+                self.push(
+                    format!("verify.kind_labels.{kind}"),
+                    format!("unknown label {label:?}"),
+                );
+            }
+        }
+    }
+
+    fn triage(&mut self, configuration: &Configuration) {
+        let names = label_names(configuration);
+
+        for (i, rule) in configuration.triage.rules.iter().enumerate() {
+            let path = format!("triage.rules[{i}]");
+
+            if rule.prefix.trim().is_empty() {
+                self.push(format!("{path}.prefix"), "prefix is empty".to_string());
+            }
+            if rule.placeholder.is_none() && rule.comment.is_none() {
+                self.push(
+                    format!("{path}.prefix"),
+                    "the rule needs a placeholder, a comment or both".to_string(),
+                );
+            }
+            if let Some(placeholder) = &rule.placeholder {
+                if !names.contains(&placeholder.to_lowercase()) {
+                    self.push(
+                        format!("{path}.placeholder"),
+                        format!("unknown label {placeholder:?}"),
+                    );
+                }
+            }
+        }
+    }
+
+    fn commands(&mut self, configuration: &Configuration) {
+        let names = label_names(configuration);
+
+        for (i, prefix) in configuration.commands.labels.iter().enumerate() {
+            let prefix = format!("{}/", prefix.to_lowercase());
+            if !names.iter().any(|name| name.starts_with(&prefix)) {
+                self.push(
+                    format!("commands.labels[{i}]"),
+                    format!("no label starts with {prefix:?}"),
+                );
+            }
+        }
+
+        let mut permissions: Vec<&String> = configuration.commands.permissions.keys().collect();
+        permissions.sort();
+        for name in permissions {
+            let name_lowercase = name.to_lowercase();
+            let command = name_lowercase
+                .strip_prefix("remove-")
+                .unwrap_or(&name_lowercase);
+            let known = COMMANDS.contains(&name_lowercase.as_str())
+                || configuration
+                    .commands
+                    .labels
+                    .iter()
+                    .any(|prefix| prefix.eq_ignore_ascii_case(command));
+            if !known {
+                self.push(
+                    format!("commands.permissions.{name}"),
+                    format!("unknown command \"/{name}\""),
+                );
+            }
+        }
+    }
+
+    fn labels(&mut self, path: &str, labels: &[Label]) {
+        let names: HashMap<String, usize> = labels
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(i, label)| (label.name.to_lowercase(), i))
+            .collect();
+
+        for (i, label) in labels.iter().enumerate() {
+            let path = format!("{path}[{i}]");
+
+            if label.name.trim().is_empty() {
+                self.push(format!("{path}.name"), "name is empty".to_string());
+            }
+            if let Some(first) = names.get(&label.name.to_lowercase()) {
+                if *first != i {
+                    self.push(
+                        format!("{path}.name"),
+                        format!(
+                            "duplicate label {:?}, first defined at index {first}",
+                            label.name
+                        ),
+                    );
+                }
+            }
+            if label.color.len() != 6 || !label.color.chars().all(|c| c.is_ascii_hexdigit()) {
+                self.push(
+                    format!("{path}.color"),
+                    format!("invalid color {:?}, expected rrggbb", label.color),
+                );
+            }
+            for (j, previous) in label.previously.iter().enumerate() {
+                if names.contains_key(&previous.to_lowercase()) {
+                    self.push(
+                        format!("{path}.previously[{j}]"),
+                        format!("former name {previous:?} is also a label"),
+                    );
+                }
+            }
+        }
+    }
+
+    fn milestones(&mut self, path: &str, milestones: &[Milestone]) {
+        let titles: HashMap<&str, usize> = milestones
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(i, milestone)| (milestone.title.as_str(), i))
+            .collect();
+
+        for (i, milestone) in milestones.iter().enumerate() {
+            let path = format!("{path}[{i}]");
+
+            if milestone.title.trim().is_empty() {
+                self.push(format!("{path}.title"), "title is empty".to_string());
+            }
+            if let Some(first) = titles.get(milestone.title.as_str()) {
+                if *first != i {
+                    self.push(
+                        format!("{path}.title"),
+                        format!(
+                            "duplicate milestone {:?}, first defined at index {first}",
+                            milestone.title
+                        ),
+                    );
+                }
+            }
+            if let Some(replaces) = &milestone.replaces {
+                let message = if replaces.trim().is_empty() {
+                    Some("replaces is empty".to_string())
+                } else if *replaces == milestone.title {
+                    Some("a milestone can not replace itself".to_string())
+                } else {
+                    None
+                };
+                if let Some(message) = message {
+                    self.push(format!("{path}.replaces"), message);
+                }
+            }
+        }
+    }
+}
+
+// COMMANDS are the slash commands besides the label ones, see `pkg/slash`.
+const COMMANDS: &[&str] = &[
+    "milestone",
+    "remove-milestone",
+    "assign",
+    "unassign",
+    "cherry-pick",
+    "close",
+    "reopen",
+    "retitle",
+];
+
+// label_names returns every label name defined in the configuration, lowercase.
+fn label_names(configuration: &Configuration) -> HashSet<String> {
+    configuration
+        .labels
+        .iter()
+        .chain(configuration.groups.labels.values().flatten())
+        .chain(configuration.repos.iter().flat_map(|r| &r.labels.extra))
+        .map(|label| label.name.to_lowercase())
+        .collect()
+}
+
+// locations maps the path of every node in the document to its position.
+fn locations(source: &str) -> HashMap<String, (usize, usize)> {
+    let mut locations = HashMap::new();
+
+    let Ok(mut loader) = Loader::new(Progress::Str(source)) else {
+        return locations;
+    };
+    if let Some(document) = loader.next_document() {
+        let mut pos = 0;
+        walk(&document.events, &mut pos, String::new(), &mut locations);
+    }
+
+    locations
+}
+
+fn walk(
+    events: &[(Event, Mark)],
+    pos: &mut usize,
+    path: String,
+    locations: &mut HashMap<String, (usize, usize)>,
+) {
+    let Some((event, mark)) = events.get(*pos) else {
+        return;
+    };
+    *pos += 1;
+
+    // libyml marks are 0-based
+    locations.insert(
+        path.clone(),
+        (mark.line() as usize + 1, mark.column() as usize + 1),
+    );
+
+    match event {
+        Event::MappingStart(_) => loop {
+            match events.get(*pos) {
+                Some((Event::MappingEnd, _)) | None => {
+                    *pos += 1;
+                    break;
+                }
+                Some((Event::Scalar(key), _)) => {
+                    *pos += 1;
+                    let key = String::from_utf8_lossy(&key.value);
+                    let child = match path.is_empty() {
+                        true => key.to_string(),
+                        false => format!("{path}.{key}"),
+                    };
+                    walk(events, pos, child, locations);
+                }
+                // Complex keys are not used in the configuration
+                Some(_) => {
+                    walk(events, pos, format!("{path}.?"), locations);
+                    walk(events, pos, format!("{path}.?"), locations);
+                }
+            }
+        },
+        Event::SequenceStart(_) => {
+            let mut i = 0;
+            loop {
+                match events.get(*pos) {
+                    Some((Event::SequenceEnd, _)) | None => {
+                        *pos += 1;
+                        break;
+                    }
+                    Some(_) => {
+                        walk(events, pos, format!("{path}[{i}]"), locations);
+                        i += 1;
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+// locate finds the position of the path, or of its closest parent when the
+// value is missing from the document.
+fn locate(locations: &HashMap<String, (usize, usize)>, path: &str) -> (usize, usize) {
+    let mut path = path;
+    loop {
+        if let Some(location) = locations.get(path) {
+            return *location;
+        }
+        match path.rfind(['.', '[']) {
+            Some(index) => path = &path[..index],
+            None => return (1, 1),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::validate::validate;
+
+    #[test]
+    fn valid() {
+        let source = include_str!("../config.yaml");
+        assert_eq!(Vec::<String>::new(), problems(source));
+    }
+
+    fn problems(source: &str) -> Vec<String> {
+        validate(source).iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn all_problems() {
+        let source = r#"
+repos:
+  - org: org
+    repo: repo
+  - org: org
+    repo: repo
+    labels:
+      include: [operatr]
+labels:
+  - name: kind/bug
+    color: ededeg
+  - name: kind/feature
+    color: c7def8
+    previously:
+      - kind/bug
+  - name: Kind/Bug
+    color: e11d21
+milestones:
+  - title: Trustify 2.0.0
+    state: opne
+    due: next week
+  - title: Trustify 2.1.0
+    replaces: Trustify 2.1.0
+verify:
+  kind_labels:
+    bug: kind/bug
+    feature: kind/feat
+triage:
+  rules:
+    - prefix: kind/
+      placeholder: needs-kind
+label_templates:
+  - name: cherry-pick/release
+    color: fef2a0
+    branches: release-*
+commands:
+  labels: [kind, area]
+  permissions:
+    kind: anyone
+    retitel: author
+"#;
+        assert_eq!(
+            vec![
+                "6:11: repos[1].repo: duplicate repo org/repo",
+                "8:17: repos[1].labels.include[0]: unknown labels group \"operatr\"",
+                "11:12: labels[0].color: invalid color \"ededeg\", expected rrggbb",
+                "15:9: labels[1].previously[0]: former name \"kind/bug\" is also a label",
+                "16:11: labels[2].name: duplicate label \"Kind/Bug\", first defined at index 0",
+                "20:12: milestones[0].state: invalid state \"opne\", expected open or closed",
+                "21:10: milestones[0].due: invalid due date \"next week\", expected YYYY-MM-DD, RFC 3339 or +N[dw]",
+                "23:15: milestones[1].replaces: a milestone can not replace itself",
+                "27:14: verify.kind_labels.feature: unknown label \"kind/feat\"",
+                "31:20: triage.rules[0].placeholder: unknown label \"needs-kind\"",
+                "33:11: label_templates[0].name: name \"cherry-pick/release\" must contain {branch} once",
+                "37:18: commands.labels[1]: no label starts with \"area/\"",
+                "40:14: commands.permissions.retitel: unknown command \"/retitel\"",
+            ],
+            problems(source)
+        );
+    }
+
+    #[test]
+    fn syntax_error() {
+        let source =
+            "repos:\n  - org: org\n    repo: repo\nlabels: []\nmilestones:\n  - title: [\n";
+        let problems = validate(source);
+        assert_eq!(1, problems.len());
+        assert_eq!(7, problems[0].line);
+    }
+
+    #[test]
+    fn missing_field() {
+        let source = "repos: []\nlabels:\n  - name: lgtm\nmilestones: []\n";
+        let problems = validate(source);
+        assert_eq!(1, problems.len());
+        assert!(problems[0].message.contains("missing field `color`"));
+    }
+}
@@ -0,0 +1,262 @@
+use core::fmt;
+
+use crate::{convention::Convention, prefix::PRType};
+
+// PullRequest is the subset of a merged pull request needed to render it in
+// the release notes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PullRequest {
+    pub number: u64,
+    pub title: String,
+    pub author: Option<String>,
+    pub url: Option<String>,
+}
+
+// Entry is a single line of the changelog, the title has its prefix removed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub title: String,
+    pub pull_request: PullRequest,
+}
+
+// Changelog groups merged pull requests by the type derived from their title.
+//
+// PRs typed as `:ghost:` are left out, PRs whose title can't be classified end
+// up in `uncategorized` so they are never dropped silently.
+#[derive(Debug, Default, PartialEq)]
+pub struct Changelog {
+    pub breaking: Vec<Entry>,
+    pub features: Vec<Entry>,
+    pub bug_fixes: Vec<Entry>,
+    pub docs: Vec<Entry>,
+    pub infra: Vec<Entry>,
+    pub uncategorized: Vec<Entry>,
+}
+
+impl Changelog {
+    pub fn new(
+        convention: &dyn Convention,
+        pull_requests: impl IntoIterator<Item = PullRequest>,
+    ) -> Self {
+        let mut changelog = Changelog::default();
+
+        for pull_request in pull_requests {
+            let (section, title) = match convention.classify(&pull_request.title) {
+                Some(PRType::Breaking(title)) => (&mut changelog.breaking, title),
+                Some(PRType::Feature(title)) => (&mut changelog.features, title),
+                Some(PRType::BugFix(title)) => (&mut changelog.bug_fixes, title),
+                Some(PRType::Docs(title)) => (&mut changelog.docs, title),
+                Some(PRType::Infra(title)) => (&mut changelog.infra, title),
+                Some(PRType::NoNote(_)) => continue,
+                None => (
+                    &mut changelog.uncategorized,
+                    pull_request.title.trim().to_string(),
+                ),
+            };
+
+            section.push(Entry {
+                title,
+                pull_request,
+            });
+        }
+
+        changelog
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sections()
+            .iter()
+            .all(|(_, entries)| entries.is_empty())
+    }
+
+    // notes renders the body of a GitHub release, the changelog after a link
+    // to the full comparison with the previous release, or to the commits of
+    // the first one.
+    pub fn notes(&self, repository: &str, previous: Option<&str>, tag: &str) -> String {
+        let link = match previous {
+            Some(previous) => format!(
+                "https://github.com/{}/compare/{}...{}",
+                repository, previous, tag
+            ),
+            None => format!("https://github.com/{}/commits/{}", repository, tag),
+        };
+        format!("**Full Changelog**: {}\n\n{}", link, self)
+    }
+
+    fn sections(&self) -> [(&'static str, &Vec<Entry>); 6] {
+        [
+            (":warning: Breaking Changes", &self.breaking),
+            (":sparkles: Features", &self.features),
+            (":bug: Bug Fixes", &self.bug_fixes),
+            (":book: Docs", &self.docs),
+            (":seedling: Infra", &self.infra),
+            (":question: Uncategorized", &self.uncategorized),
+        ]
+    }
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "* {}", self.title)?;
+        if let Some(author) = &self.pull_request.author {
+            write!(f, " by @{}", author)?;
+        }
+        match &self.pull_request.url {
+            Some(url) => write!(f, " in {}", url),
+            None => write!(f, " (#{})", self.pull_request.number),
+        }
+    }
+}
+
+impl fmt::Display for Changelog {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (heading, entries) in self.sections() {
+            if entries.is_empty() {
+                continue;
+            }
+
+            writeln!(f, "## {}", heading)?;
+            for entry in entries {
+                writeln!(f, "{}", entry)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+// new_contributors returns the "New Contributors" section of the notes GitHub
+// generates, None when nobody contributed for the first time.
+pub fn new_contributors(generated: &str) -> Option<&str> {
+    let start = generated.find("## New Contributors")?;
+    let section = &generated[start..];
+    let end = section.find("**Full Changelog**").unwrap_or(section.len());
+    Some(section[..end].trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        changelog::{new_contributors, Changelog, PullRequest},
+        convention::{ConventionalCommits, Emoji},
+    };
+
+    fn pull_request(number: u64, title: &str) -> PullRequest {
+        PullRequest {
+            number,
+            title: title.to_string(),
+            author: Some("octocat".to_string()),
+            url: Some(format!("https://github.com/org/repo/pull/{}", number)),
+        }
+    }
+
+    #[test]
+    fn render() {
+        let changelog = Changelog::new(
+            &Emoji,
+            vec![
+                pull_request(1, ":sparkles: Add new feature"),
+                pull_request(2, "[release-0.2] :bug: Fix bug"),
+                pull_request(3, "⚠️ Breaking change with raw emoji"),
+                pull_request(4, ":ghost: Don't put me in release notes"),
+                pull_request(5, "No prefix in title"),
+                pull_request(6, ":seedling: Bump dependencies"),
+                PullRequest {
+                    number: 7,
+                    title: ":book: Update documentation".to_string(),
+                    author: None,
+                    url: None,
+                },
+            ],
+        );
+
+        let expected = "\
+## :warning: Breaking Changes
+* Breaking change with raw emoji by @octocat in https://github.com/org/repo/pull/3
+
+## :sparkles: Features
+* Add new feature by @octocat in https://github.com/org/repo/pull/1
+
+## :bug: Bug Fixes
+* Fix bug by @octocat in https://github.com/org/repo/pull/2
+
+## :book: Docs
+* Update documentation (#7)
+
+## :seedling: Infra
+* Bump dependencies by @octocat in https://github.com/org/repo/pull/6
+
+## :question: Uncategorized
+* No prefix in title by @octocat in https://github.com/org/repo/pull/5
+
+";
+
+        assert_eq!(expected, changelog.to_string());
+    }
+
+    #[test]
+    fn notes() {
+        let changelog = Changelog::new(&Emoji, vec![pull_request(1, ":bug: Fix bug")]);
+        assert_eq!(
+            "\
+**Full Changelog**: https://github.com/org/repo/compare/v0.2.0...v0.2.1
+
+## :bug: Bug Fixes
+* Fix bug by @octocat in https://github.com/org/repo/pull/1
+
+",
+            changelog.notes("org/repo", Some("v0.2.0"), "v0.2.1")
+        );
+        assert_eq!(
+            "\
+**Full Changelog**: https://github.com/org/repo/commits/v0.1.0
+
+## :bug: Bug Fixes
+* Fix bug by @octocat in https://github.com/org/repo/pull/1
+
+",
+            changelog.notes("org/repo", None, "v0.1.0")
+        );
+    }
+
+    #[test]
+    fn contributors() {
+        let generated = "\
+## What's Changed
+* :bug: Fix bug by @octocat in https://github.com/org/repo/pull/1
+
+## New Contributors
+* @octocat made their first contribution in https://github.com/org/repo/pull/1
+
+**Full Changelog**: https://github.com/org/repo/compare/v0.2.0...v0.2.1";
+        assert_eq!(
+            Some("## New Contributors\n* @octocat made their first contribution in https://github.com/org/repo/pull/1"),
+            new_contributors(generated)
+        );
+        assert_eq!(
+            None,
+            new_contributors("**Full Changelog**: https://github.com/org/repo/commits/v0.1.0")
+        );
+    }
+
+    #[test]
+    fn empty() {
+        let changelog = Changelog::new(&Emoji, vec![pull_request(1, ":ghost: Nothing to see")]);
+        assert!(changelog.is_empty());
+        assert_eq!("", changelog.to_string());
+    }
+
+    #[test]
+    fn conventional_commits() {
+        let changelog = Changelog::new(
+            &ConventionalCommits,
+            vec![
+                pull_request(1, "feat(ui): Add new feature"),
+                pull_request(2, ":bug: Fix bug"),
+            ],
+        );
+
+        assert_eq!("Add new feature", changelog.features[0].title);
+        assert_eq!(":bug: Fix bug", changelog.uncategorized[0].title);
+    }
+}
@@ -0,0 +1,209 @@
+use regex::Regex;
+
+use crate::{
+    error::{Error, Result},
+    prefix::{strip_wip_and_tag, PRType},
+};
+
+// Convention knows how a PR title encodes its type. Every convention maps onto
+// the same PRType categories so verification and release notes don't need to
+// know which one a repository uses.
+pub trait Convention {
+    // parse validates the title, it is used to enforce the convention at PR time.
+    fn parse(&self, title: &str) -> Result<PRType>;
+
+    // classify is used for release notes, it may accept titles that `parse`
+    // rejects so already merged PRs are still grouped.
+    fn classify(&self, title: &str) -> Option<PRType> {
+        self.parse(title).ok()
+    }
+
+    // format renders a title of the type in this convention, `parse` accepts it
+    fn format(&self, pr_type: &PRType) -> String;
+}
+
+// Emoji is the kubebuilder style `:sparkles: Add new feature` convention.
+pub struct Emoji;
+
+impl Convention for Emoji {
+    fn parse(&self, title: &str) -> Result<PRType> {
+        PRType::from_title(title)
+    }
+
+    fn classify(&self, title: &str) -> Option<PRType> {
+        match PRType::from_title(title) {
+            Ok(pr_type) => Some(pr_type),
+            Err(Error::InvalidTitle {
+                title,
+                emoji: Some(_),
+            }) => PRType::from_emoji(&title),
+            Err(_) => None,
+        }
+    }
+
+    fn format(&self, pr_type: &PRType) -> String {
+        format!("{} {}", pr_type.alias(), pr_type.title())
+    }
+}
+
+// ConventionalCommits is the https://www.conventionalcommits.org convention,
+// `feat: Add new feature`, `fix(scope): Fix bug` or `feat!: Breaking change`.
+pub struct ConventionalCommits;
+
+impl Convention for ConventionalCommits {
+    fn parse(&self, title: &str) -> Result<PRType> {
+        let regex =
+            Regex::new(r"^(?P<kind>[A-Za-z]+)(?:\([^()]*\))?(?P<breaking>!)?:\s*(?P<title>.*)$")
+                .unwrap();
+
+        let value = strip_wip_and_tag(title);
+
+        let Some(captures) = regex.captures(&value) else {
+            return Err(Error::InvalidConventionalTitle {
+                title: value,
+                kind: None,
+            });
+        };
+
+        let kind = captures["kind"].to_lowercase();
+        let title = captures["title"].trim().to_string();
+
+        if title.is_empty() {
+            return Err(Error::InvalidConventionalTitle {
+                title: value,
+                kind: Some(kind),
+            });
+        }
+
+        if captures.name("breaking").is_some() {
+            return Ok(PRType::Breaking(title));
+        }
+
+        match kind.as_str() {
+            "feat" => Ok(PRType::Feature(title)),
+            "fix" => Ok(PRType::BugFix(title)),
+            "docs" => Ok(PRType::Docs(title)),
+            "build" | "chore" | "ci" | "perf" | "refactor" | "revert" | "style" | "test" => {
+                Ok(PRType::Infra(title))
+            }
+            _ => Err(Error::InvalidConventionalTitle {
+                title: value,
+                kind: Some(kind),
+            }),
+        }
+    }
+
+    fn format(&self, pr_type: &PRType) -> String {
+        let kind = match pr_type {
+            PRType::Feature(_) => "feat",
+            PRType::BugFix(_) => "fix",
+            PRType::Docs(_) => "docs",
+            PRType::Infra(_) | PRType::NoNote(_) => "chore",
+            PRType::Breaking(_) => "feat!",
+        };
+        format!("{}: {}", kind, pr_type.title())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        convention::{Convention, ConventionalCommits, Emoji},
+        error::{Error, Result},
+        prefix::PRType,
+    };
+
+    struct TestCase {
+        pub title: &'static str,
+        pub expected_result: Result<PRType>,
+    }
+
+    #[test]
+    fn conventional_commits_cases() {
+        let test_cases = vec![
+            TestCase {
+                title: "feat: Add new feature",
+                expected_result: Ok(PRType::Feature("Add new feature".to_string())),
+            },
+            TestCase {
+                title: "fix(api): Fix bug",
+                expected_result: Ok(PRType::BugFix("Fix bug".to_string())),
+            },
+            TestCase {
+                title: "feat!: Breaking change",
+                expected_result: Ok(PRType::Breaking("Breaking change".to_string())),
+            },
+            TestCase {
+                title: "refactor(ui)!: Breaking change",
+                expected_result: Ok(PRType::Breaking("Breaking change".to_string())),
+            },
+            TestCase {
+                title: "WIP: [release-0.2] Docs: Update documentation",
+                expected_result: Ok(PRType::Docs("Update documentation".to_string())),
+            },
+            TestCase {
+                title: "chore(deps): Bump serde",
+                expected_result: Ok(PRType::Infra("Bump serde".to_string())),
+            },
+            TestCase {
+                title: "feature: Add new feature",
+                expected_result: Err(Error::InvalidConventionalTitle {
+                    title: "feature: Add new feature".to_string(),
+                    kind: Some("feature".to_string()),
+                }),
+            },
+            TestCase {
+                title: "fix:",
+                expected_result: Err(Error::InvalidConventionalTitle {
+                    title: "fix:".to_string(),
+                    kind: Some("fix".to_string()),
+                }),
+            },
+            TestCase {
+                title: ":sparkles: Add new feature",
+                expected_result: Err(Error::InvalidConventionalTitle {
+                    title: ":sparkles: Add new feature".to_string(),
+                    kind: None,
+                }),
+            },
+        ];
+
+        for tc in test_cases {
+            let pr = ConventionalCommits.parse(tc.title);
+            assert_eq!(tc.expected_result, pr, "title: {}", tc.title);
+        }
+    }
+
+    #[test]
+    fn emoji_classify_accepts_raw_emoji() {
+        assert!(Emoji.parse("✨ Add new feature").is_err());
+        assert_eq!(
+            Some(PRType::Feature("Add new feature".to_string())),
+            Emoji.classify("✨ Add new feature")
+        );
+    }
+
+    #[test]
+    fn format() {
+        let pr_types = [
+            PRType::Feature("Add new feature".to_string()),
+            PRType::BugFix("Fix bug".to_string()),
+            PRType::Breaking("Breaking change".to_string()),
+            PRType::Docs("Update documentation".to_string()),
+        ];
+
+        for pr_type in pr_types {
+            assert_eq!(Ok(&pr_type), Emoji.parse(&Emoji.format(&pr_type)).as_ref());
+            assert_eq!(
+                Ok(&pr_type),
+                ConventionalCommits
+                    .parse(&ConventionalCommits.format(&pr_type))
+                    .as_ref()
+            );
+        }
+        assert_eq!(
+            "fix: Fix bug",
+            ConventionalCommits.format(&PRType::BugFix("Fix bug".to_string()))
+        );
+    }
+}
@@ -9,4 +9,9 @@ pub enum Error {
         title: String,
         emoji: Option<String>,
     },
+
+    #[error(
+        "Invalid Conventional Commits title (title: {title}, type: {kind:#?}).\nValid types are:\nfeat\nfix\ndocs\nbuild\nchore\nci\nperf\nrefactor\nrevert\nstyle\ntest\nAppend ! to the type for breaking changes, e.g. feat!: or feat(scope)!:\n"
+    )]
+    InvalidConventionalTitle { title: String, kind: Option<String> },
 }
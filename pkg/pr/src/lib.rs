@@ -1,2 +1,4 @@
+pub mod changelog;
+pub mod convention;
 pub mod error;
 pub mod prefix;
@@ -36,20 +36,64 @@ impl fmt::Display for PRType {
     }
 }
 
-impl PRType {
-    pub fn from_title(value: &str) -> Result<Self> {
+// Backport is a PR carrying changes already merged on main to a release
+// branch, titled `:seedling: [release-0.2] :bug: Fix bug` by the backport
+// action or `[release-0.2] :bug: Fix bug` by hand.
+#[derive(Debug, PartialEq)]
+pub struct Backport {
+    // Branch of the tag, e.g. `release-0.2`
+    pub branch: String,
+    // Title of the original PR
+    pub title: String,
+}
+
+impl Backport {
+    pub fn from_title(value: &str) -> Option<Self> {
         let wip_regex = Regex::new(r"(?i)^\W?WIP\W").unwrap();
-        let tag_regex = Regex::new(r"^\[[\w.-]*]").unwrap();
+        let backport_regex = Regex::new(
+            r"^(?:(?::seedling:|🌱)\s*)?\[(?P<branch>release-\d+\.\d+)]\s*(?P<title>.*)$",
+        )
+        .unwrap();
 
-        // Remove the WIP prefix if found.
         let value = wip_regex.replace_all(value, "");
+        let captures = backport_regex.captures(value.trim())?;
 
-        // Trim to remove spaces after WIP.
-        let value = value.trim();
+        Some(Backport {
+            branch: captures["branch"].to_string(),
+            title: captures["title"].trim().to_string(),
+        })
+    }
+}
 
-        // Remove a tag prefix if found.
-        let value = tag_regex.replace_all(value, "");
-        let value = value.trim();
+// Backports are titled like the backport action did, keeping the type of the
+// original PR after the tag.
+impl fmt::Display for Backport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} [{}] {}", PREFIX_INFRA.0, self.branch, self.title)
+    }
+}
+
+// strip_wip_and_tag removes the "WIP" marker and a leading "[tag]" so only the
+// part of the title that carries the type is left.
+pub(crate) fn strip_wip_and_tag(value: &str) -> String {
+    let wip_regex = Regex::new(r"(?i)^\W?WIP\W").unwrap();
+    let tag_regex = Regex::new(r"^\[[\w.-]*]").unwrap();
+
+    // Remove the WIP prefix if found.
+    let value = wip_regex.replace_all(value, "");
+
+    // Trim to remove spaces after WIP.
+    let value = value.trim();
+
+    // Remove a tag prefix if found.
+    let value = tag_regex.replace_all(value, "");
+    value.trim().to_string()
+}
+
+impl PRType {
+    pub fn from_title(value: &str) -> Result<Self> {
+        let value = strip_wip_and_tag(value);
+        let value = value.as_str();
 
         if value.is_empty() {
             return Err(crate::error::Error::InvalidTitle {
@@ -92,15 +136,53 @@ impl PRType {
             || value.strip_prefix(PREFIX_NO_NOTE.1).is_some()
         {
             let emoji = value.chars().next().map(|c| c.to_string());
-            return Err(crate::error::Error::InvalidTitle {
+            Err(crate::error::Error::InvalidTitle {
                 title: trust(value),
                 emoji,
-            });
+            })
         } else {
-            return Err(crate::error::Error::InvalidTitle {
+            Err(crate::error::Error::InvalidTitle {
                 title: trust(value),
                 emoji: None,
-            });
+            })
+        }
+    }
+
+    // from_emoji classifies a title that starts with the raw emoji instead of
+    // its alias. These are rejected at PR time but still need to show up in
+    // release notes for PRs that were merged before the check existed.
+    pub fn from_emoji(value: &str) -> Option<Self> {
+        let value = value.trim();
+
+        let strip =
+            |title: &str| -> String { title.trim_start_matches('\u{FE0F}').trim().to_string() };
+
+        if let Some(title) = value.strip_prefix(PREFIX_FEATURE.1) {
+            Some(PRType::Feature(strip(title)))
+        } else if let Some(title) = value.strip_prefix(PREFIX_BUG_FIX.1) {
+            Some(PRType::BugFix(strip(title)))
+        } else if let Some(title) = value.strip_prefix(PREFIX_DOCS.1) {
+            Some(PRType::Docs(strip(title)))
+        } else if let Some(title) = value.strip_prefix(PREFIX_INFRA.1) {
+            Some(PRType::Infra(strip(title)))
+        } else if let Some(title) = value.strip_prefix(PREFIX_BREAKING.1) {
+            Some(PRType::Breaking(strip(title)))
+        } else {
+            value
+                .strip_prefix(PREFIX_NO_NOTE.1)
+                .map(|title| PRType::NoNote(strip(title)))
+        }
+    }
+
+    // alias is the prefix of the type in titles, e.g. `:bug:`
+    pub fn alias(&self) -> &'static str {
+        match self {
+            PRType::Feature(_) => PREFIX_FEATURE.0,
+            PRType::BugFix(_) => PREFIX_BUG_FIX.0,
+            PRType::Docs(_) => PREFIX_DOCS.0,
+            PRType::Infra(_) => PREFIX_INFRA.0,
+            PRType::Breaking(_) => PREFIX_BREAKING.0,
+            PRType::NoNote(_) => PREFIX_NO_NOTE.0,
         }
     }
 
@@ -118,7 +200,10 @@ impl PRType {
 
 #[cfg(test)]
 mod tests {
-    use crate::{error::Result, prefix::PRType};
+    use crate::{
+        error::Result,
+        prefix::{Backport, PRType},
+    };
 
     struct TestCase {
         pub title: &'static str,
@@ -201,4 +286,33 @@ mod tests {
             assert_eq!(tc.expected_result, pr);
         }
     }
+
+    #[test]
+    fn backport() {
+        let expected = Some(Backport {
+            branch: "release-0.2".to_string(),
+            title: ":bug: Fix bug".to_string(),
+        });
+        assert_eq!(
+            expected,
+            Backport::from_title(":seedling: [release-0.2] :bug: Fix bug")
+        );
+        assert_eq!(
+            expected,
+            Backport::from_title("WIP: [release-0.2] :bug: Fix bug")
+        );
+        assert_eq!(None, Backport::from_title("[docs] :book: Update docs"));
+        assert_eq!(None, Backport::from_title(":bug: [release-0.2] Fix bug"));
+
+        let title = Backport {
+            branch: "release-0.2".to_string(),
+            title: ":bug: Fix bug".to_string(),
+        }
+        .to_string();
+        assert_eq!(":seedling: [release-0.2] :bug: Fix bug", title);
+        assert_eq!(
+            Some(":bug: Fix bug".to_string()),
+            Backport::from_title(&title).map(|backport| backport.title)
+        );
+    }
 }
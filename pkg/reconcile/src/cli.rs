@@ -0,0 +1,34 @@
+// Output is the format the plan is printed in.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum Output {
+    // One line per change, for humans
+    Text,
+    Json,
+    #[default]
+    Yaml,
+}
+
+// PlanArgs are the arguments shared by every reconciler.
+#[derive(clap::Args, Debug, Default)]
+pub struct PlanArgs {
+    /// Make mutating changes via GitHub API
+    #[arg(long, default_value = "false")]
+    pub confirm: bool,
+
+    /// Format of the plan
+    #[arg(long, value_enum, default_value_t = Output::Yaml)]
+    pub output: Output,
+
+    /// Exit with code 2 when changes are pending, no mutations are made
+    #[arg(long, default_value = "false", conflicts_with = "confirm")]
+    pub detect_drift: bool,
+
+    /// Save the plan and the state it was computed against to this file
+    #[arg(long, default_value = "")]
+    pub out: String,
+
+    /// Use the plan saved with --out instead of computing one, it is rejected
+    /// when the repositories changed since
+    #[arg(long, default_value = "", conflicts_with = "out")]
+    pub plan: String,
+}
@@ -0,0 +1,28 @@
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("invalid plan: {0}")]
+    InvalidPlan(String),
+
+    #[error("plan is stale, {org}/{repo} changed since it was computed")]
+    StalePlan { org: String, repo: String },
+
+    #[error(transparent)]
+    Action(#[from] action::error::Error),
+
+    #[error(transparent)]
+    Octocrab(#[from] octocrab::Error),
+
+    #[error(transparent)]
+    SerdeJson(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    SerdeYml(#[from] serde_yml::Error),
+
+    #[error(transparent)]
+    IO(#[from] std::io::Error),
+
+    #[error(transparent)]
+    Any(#[from] anyhow::Error),
+}
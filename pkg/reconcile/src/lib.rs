@@ -0,0 +1,5 @@
+pub mod cli;
+pub mod error;
+pub mod plan;
+pub mod resource;
+pub mod testing;
@@ -0,0 +1,501 @@
+use std::{fmt::Display, fs, process::ExitCode};
+
+use action::commands::ActionCommand;
+use config::types::Repo;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    cli::{Output, PlanArgs},
+    error::{Error, Result},
+    resource::Resource,
+};
+
+// API_VERSION identifies the schema of the plan document, bump it when
+// making incompatible changes.
+pub const API_VERSION: &str = "release-tools/v1";
+
+// DRIFT_EXIT_CODE is returned with `--detect-drift` when changes are pending.
+pub const DRIFT_EXIT_CODE: u8 = 2;
+
+// Update is a change scoped to the repository it applies to.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Update<C> {
+    pub org: String,
+    pub repo: String,
+    pub why: C,
+}
+
+// Observation is the state of a repository the updates were computed against.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Observation<O> {
+    pub org: String,
+    pub repo: String,
+    pub state: O,
+}
+
+// Plan is the list of changes needed to reconcile every repository.
+//
+// It also records the observed state of every repository with updates, a
+// saved plan is only applied while GitHub still matches that state.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Plan<O, C> {
+    pub api_version: String,
+    pub kind: String,
+    pub observed: Vec<Observation<O>>,
+    pub updates: Vec<Update<C>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<Update<String>>,
+}
+
+// Document is what gets printed, the observed state is left out as it is only
+// needed by saved plans.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Document<'a, C> {
+    api_version: &'a str,
+    kind: &'a str,
+    updates: &'a [Update<C>],
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    warnings: &'a [Update<String>],
+}
+
+impl<O, C> Plan<O, C> {
+    pub async fn compute<R>(resource: &R, repos: &[Repo]) -> Result<Self>
+    where
+        R: Resource<Observed = O, Change = C>,
+    {
+        let mut observed: Vec<Observation<O>> = vec![];
+        let mut updates: Vec<Update<C>> = vec![];
+        let mut warnings: Vec<Update<String>> = vec![];
+        for repo in repos {
+            let desired = resource.desired(repo);
+            let state = resource.observe(&repo.org, &repo.repo).await?;
+
+            warnings.extend(
+                resource
+                    .warnings(&desired, &state)
+                    .into_iter()
+                    .map(|why| Update {
+                        org: repo.org.clone(),
+                        repo: repo.repo.clone(),
+                        why,
+                    }),
+            );
+
+            let changes = resource.diff(&desired, &state);
+            if changes.is_empty() {
+                continue;
+            }
+
+            updates.extend(changes.into_iter().map(|why| Update {
+                org: repo.org.clone(),
+                repo: repo.repo.clone(),
+                why,
+            }));
+            observed.push(Observation {
+                org: repo.org.clone(),
+                repo: repo.repo.clone(),
+                state,
+            });
+        }
+
+        Ok(Plan {
+            api_version: API_VERSION.to_string(),
+            kind: R::KIND.to_string(),
+            observed,
+            updates,
+            warnings,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.updates.is_empty()
+    }
+
+    pub async fn apply<R>(&self, resource: &R) -> Result<()>
+    where
+        R: Resource<Observed = O, Change = C>,
+    {
+        for update in &self.updates {
+            resource
+                .apply(&update.org, &update.repo, &update.why)
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+impl<O, C> Plan<O, C>
+where
+    O: Serialize + DeserializeOwned + PartialEq,
+    C: Serialize + DeserializeOwned + Display,
+{
+    // render formats the plan, json and yaml are the stable documents meant
+    // for other tools, text is meant for humans.
+    pub fn render(&self, output: Output) -> Result<String> {
+        let document = Document {
+            api_version: &self.api_version,
+            kind: &self.kind,
+            updates: &self.updates,
+            warnings: &self.warnings,
+        };
+
+        let data = match output {
+            Output::Json => serde_json::to_string_pretty(&document)?,
+            Output::Yaml => serde_yml::to_string(&document)?,
+            Output::Text => self
+                .updates
+                .iter()
+                .map(|update| format!("{}/{}: {}", update.org, update.repo, update.why))
+                .collect::<Vec<String>>()
+                .join("\n"),
+        };
+        Ok(data)
+    }
+
+    // save writes the plan, observed state included, as json.
+    pub fn save(&self, path: &str) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    // load reads a plan written by `save` for the given resource kind.
+    pub fn load(path: &str, kind: &str) -> Result<Self> {
+        let plan: Self = serde_json::from_str(&fs::read_to_string(path)?)?;
+
+        if plan.api_version != API_VERSION {
+            return Err(Error::InvalidPlan(format!(
+                "unsupported apiVersion {:?}, expected {:?}",
+                plan.api_version, API_VERSION
+            )));
+        }
+        if plan.kind != kind {
+            return Err(Error::InvalidPlan(format!(
+                "plan is for {:?}, expected {:?}",
+                plan.kind, kind
+            )));
+        }
+        if let Some(update) = plan.updates.iter().find(|update| {
+            !plan
+                .observed
+                .iter()
+                .any(|o| o.org == update.org && o.repo == update.repo)
+        }) {
+            return Err(Error::InvalidPlan(format!(
+                "no observed state for {}/{}",
+                update.org, update.repo
+            )));
+        }
+
+        Ok(plan)
+    }
+
+    // verify observes every repository again, the plan is stale as soon as
+    // one of them changed since it was computed.
+    pub async fn verify<R>(&self, resource: &R) -> Result<()>
+    where
+        R: Resource<Observed = O, Change = C>,
+    {
+        for observation in &self.observed {
+            let state = resource
+                .observe(&observation.org, &observation.repo)
+                .await?;
+            if state != observation.state {
+                return Err(Error::StalePlan {
+                    org: observation.org.clone(),
+                    repo: observation.repo.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+// run computes the plan for all repos, or loads a saved one, prints it and
+// applies it when confirmed.
+//
+// Notices and warnings are only sent with the text output so json and yaml can
+// be piped into other tools, the documents list the warnings instead. Resources
+// report the progress of apply on stderr for the same reason.
+pub async fn run<R: Resource>(resource: &R, repos: &[Repo], args: &PlanArgs) -> Result<ExitCode> {
+    let plan = match args.plan.as_str() {
+        "" => Plan::compute(resource, repos).await?,
+        path => {
+            let plan = Plan::load(path, R::KIND)?;
+            plan.verify(resource).await?;
+            plan
+        }
+    };
+    if !args.out.is_empty() {
+        plan.save(&args.out)?;
+    }
+
+    let notice = |message: &str| {
+        if args.output == Output::Text {
+            ActionCommand::Notice(message.to_string()).send_command();
+        }
+    };
+
+    if args.output == Output::Text {
+        for warning in &plan.warnings {
+            ActionCommand::Warning(format!("{}/{}: {}", warning.org, warning.repo, warning.why))
+                .send_command();
+        }
+    }
+
+    if plan.is_empty() {
+        if args.output == Output::Text {
+            notice("Yay, there are no changes to be made");
+        } else {
+            println!("{}", plan.render(args.output)?);
+        }
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    notice("Changes will be made");
+    println!("{}", plan.render(args.output)?);
+
+    if args.detect_drift {
+        if args.output == Output::Text {
+            ActionCommand::Error(format!(
+                "{} drifted from the configuration, {} changes are pending",
+                R::KIND,
+                plan.updates.len()
+            ))
+            .send_command();
+        }
+        return Ok(ExitCode::from(DRIFT_EXIT_CODE));
+    }
+
+    if !args.confirm {
+        notice("Running without confirm, no mutations will be made");
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    plan.apply(resource).await?;
+
+    notice("Yay");
+
+    Ok(ExitCode::SUCCESS)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, fmt, process::ExitCode, sync::Mutex};
+
+    use async_trait::async_trait;
+    use config::types::Repo;
+    use serde::{Deserialize, Serialize};
+
+    use crate::{
+        cli::{Output, PlanArgs},
+        error::{Error, Result},
+        plan::{run, Plan, DRIFT_EXIT_CODE},
+        resource::Resource,
+    };
+
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    enum Why {
+        Missing(String),
+    }
+
+    impl fmt::Display for Why {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                Why::Missing(topic) => write!(f, "add topic {topic}"),
+            }
+        }
+    }
+
+    // Topics keeps the topics of every repository in memory.
+    struct Topics {
+        wanted: Vec<String>,
+        current: Mutex<HashMap<String, Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl Resource for Topics {
+        const KIND: &'static str = "Topics";
+
+        type Desired = String;
+        type Observed = Vec<String>;
+        type Change = Why;
+
+        fn desired(&self, _repo: &Repo) -> Vec<String> {
+            self.wanted.clone()
+        }
+
+        async fn observe(&self, org: &str, repo: &str) -> Result<Vec<String>> {
+            let current = self.current.lock().unwrap();
+            Ok(current
+                .get(&format!("{org}/{repo}"))
+                .cloned()
+                .unwrap_or_default())
+        }
+
+        fn diff(&self, desired: &[String], observed: &Vec<String>) -> Vec<Why> {
+            desired
+                .iter()
+                .filter(|topic| !observed.contains(topic))
+                .map(|topic| Why::Missing(topic.clone()))
+                .collect()
+        }
+
+        async fn apply(&self, org: &str, repo: &str, change: &Why) -> Result<()> {
+            let mut current = self.current.lock().unwrap();
+            let Why::Missing(topic) = change;
+            current
+                .entry(format!("{org}/{repo}"))
+                .or_default()
+                .push(topic.clone());
+            Ok(())
+        }
+    }
+
+    fn args(confirm: bool, output: Output, detect_drift: bool) -> PlanArgs {
+        PlanArgs {
+            confirm,
+            output,
+            detect_drift,
+            ..Default::default()
+        }
+    }
+
+    fn repo(name: &str) -> Repo {
+        Repo {
+            org: "org".to_string(),
+            repo: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn plan_and_apply() {
+        let topics = Topics {
+            wanted: vec!["rust".to_string(), "sbom".to_string()],
+            current: Mutex::new(HashMap::from([(
+                "org/a".to_string(),
+                vec!["rust".to_string()],
+            )])),
+        };
+        let repos = vec![repo("a"), repo("b")];
+
+        let plan = Plan::compute(&topics, &repos).await.unwrap();
+        let changes: Vec<(&str, &Why)> = plan
+            .updates
+            .iter()
+            .map(|update| (update.repo.as_str(), &update.why))
+            .collect();
+        assert_eq!(
+            vec![
+                ("a", &Why::Missing("sbom".to_string())),
+                ("b", &Why::Missing("rust".to_string())),
+                ("b", &Why::Missing("sbom".to_string())),
+            ],
+            changes
+        );
+
+        assert_eq!(
+            "org/a: add topic sbom\norg/b: add topic rust\norg/b: add topic sbom",
+            plan.render(Output::Text).unwrap()
+        );
+
+        let document: serde_json::Value =
+            serde_json::from_str(&plan.render(Output::Json).unwrap()).unwrap();
+        assert_eq!("release-tools/v1", document["apiVersion"]);
+        assert_eq!("Topics", document["kind"]);
+        assert_eq!("sbom", document["updates"][0]["why"]["Missing"]);
+
+        // Without confirm nothing changes.
+        let code = run(&topics, &repos, &args(false, Output::Yaml, false))
+            .await
+            .unwrap();
+        assert_eq!(ExitCode::SUCCESS, code);
+        assert_eq!(
+            3,
+            Plan::compute(&topics, &repos).await.unwrap().updates.len()
+        );
+
+        let code = run(&topics, &repos, &args(false, Output::Json, true))
+            .await
+            .unwrap();
+        assert_eq!(ExitCode::from(DRIFT_EXIT_CODE), code);
+
+        run(&topics, &repos, &args(true, Output::Text, false))
+            .await
+            .unwrap();
+        assert!(Plan::compute(&topics, &repos).await.unwrap().is_empty());
+
+        let code = run(&topics, &repos, &args(false, Output::Text, true))
+            .await
+            .unwrap();
+        assert_eq!(ExitCode::SUCCESS, code);
+    }
+
+    #[tokio::test]
+    async fn saved_plan() {
+        let topics = Topics {
+            wanted: vec!["rust".to_string()],
+            current: Mutex::new(HashMap::from([(
+                "org/a".to_string(),
+                vec!["sbom".to_string()],
+            )])),
+        };
+        let repos = vec![repo("a"), repo("b")];
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("plan.json").to_str().unwrap().to_string();
+
+        let code = run(
+            &topics,
+            &repos,
+            &PlanArgs {
+                out: path.clone(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(ExitCode::SUCCESS, code);
+
+        let plan: Plan<Vec<String>, Why> = Plan::load(&path, "Topics").unwrap();
+        assert_eq!(2, plan.updates.len());
+        assert_eq!(
+            vec![("a", &vec!["sbom".to_string()]), ("b", &vec![])],
+            plan.observed
+                .iter()
+                .map(|o| (o.repo.as_str(), &o.state))
+                .collect::<Vec<_>>()
+        );
+        assert!(matches!(
+            Plan::<Vec<String>, Why>::load(&path, "Labels"),
+            Err(Error::InvalidPlan(_))
+        ));
+
+        // Someone changed org/b after the plan was reviewed.
+        topics
+            .current
+            .lock()
+            .unwrap()
+            .insert("org/b".to_string(), vec!["rust".to_string()]);
+        let apply = PlanArgs {
+            confirm: true,
+            plan: path.clone(),
+            ..Default::default()
+        };
+        assert!(matches!(
+            run(&topics, &repos, &apply).await,
+            Err(Error::StalePlan { org, repo }) if org == "org" && repo == "b"
+        ));
+        assert_eq!(
+            vec!["sbom".to_string()],
+            topics.current.lock().unwrap()["org/a"]
+        );
+
+        // Back to the reviewed state, the saved updates are applied as is.
+        topics.current.lock().unwrap().remove("org/b");
+        run(&topics, &repos, &apply).await.unwrap();
+        assert!(Plan::compute(&topics, &repos).await.unwrap().is_empty());
+    }
+}
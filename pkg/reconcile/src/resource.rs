@@ -0,0 +1,47 @@
+use std::fmt::Display;
+
+use async_trait::async_trait;
+use config::types::Repo;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::error::Result;
+
+// Resource is something we manage declaratively in every repository, like
+// labels or milestones.
+//
+// The desired state comes from config.yaml, the observed state from GitHub.
+// Diffing them produces the changes that `apply` turns into API calls.
+#[async_trait]
+pub trait Resource: Sync {
+    // KIND names the resource in the plan document, e.g. "Labels"
+    const KIND: &'static str;
+
+    // Desired is the declarative representation, e.g. `config::types::Label`
+    type Desired: Send + Sync;
+
+    // Observed is everything fetched from a repository that `diff` needs, it
+    // is saved with the plan and compared before applying it
+    type Observed: Serialize + DeserializeOwned + PartialEq + Send + Sync;
+
+    // Change is a single mutation, it is printed as part of the plan
+    type Change: Serialize + DeserializeOwned + Display + Send + Sync;
+
+    // desired returns the state the repository should be in
+    fn desired(&self, repo: &Repo) -> Vec<Self::Desired>;
+
+    // observe fetches the current state of the repository
+    async fn observe(&self, org: &str, repo: &str) -> Result<Self::Observed>;
+
+    // diff computes the changes needed to go from observed to desired
+    fn diff(&self, desired: &[Self::Desired], observed: &Self::Observed) -> Vec<Self::Change>;
+
+    // warnings reports what needs attention but is not a change, e.g. a
+    // reference to something missing in the repository
+    fn warnings(&self, _desired: &[Self::Desired], _observed: &Self::Observed) -> Vec<String> {
+        vec![]
+    }
+
+    // apply makes the change in the repository, progress goes to stderr as
+    // stdout holds the plan document
+    async fn apply(&self, org: &str, repo: &str, change: &Self::Change) -> Result<()>;
+}
@@ -0,0 +1,43 @@
+use config::types::Repo;
+
+use crate::{
+    cli::{Output, PlanArgs},
+    plan::{run, Plan},
+    resource::Resource,
+};
+
+// Fixtures shared by the tests of the resources, they go with
+// `action::fake::FakeGitHub`.
+
+// repos returns the repositories of the "org" organization with the names
+pub fn repos(names: &[&str]) -> Vec<Repo> {
+    names
+        .iter()
+        .map(|name| Repo {
+            org: "org".to_string(),
+            repo: name.to_string(),
+            ..Default::default()
+        })
+        .collect()
+}
+
+// render returns the plan of the resource as text
+pub async fn render<R: Resource>(resource: &R, repos: &[Repo]) -> String {
+    Plan::compute(resource, repos)
+        .await
+        .unwrap()
+        .render(Output::Text)
+        .unwrap()
+}
+
+// apply_and_recompute applies the plan as `--confirm` does, then returns the
+// plan that is left as text, it is empty once the resource is reconciled.
+pub async fn apply_and_recompute<R: Resource>(resource: &R, repos: &[Repo]) -> String {
+    let args = PlanArgs {
+        confirm: true,
+        output: Output::Text,
+        ..Default::default()
+    };
+    run(resource, repos, &args).await.unwrap();
+    render(resource, repos).await
+}
@@ -0,0 +1,216 @@
+use std::fmt;
+
+use crate::{
+    error::{Error, Result},
+    parse::Invocation,
+};
+
+// Command is what an invocation asks for.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    // Prefix, Name, e.g. `/kind bug` adds `kind/bug`
+    AddLabel(String, String),
+    // Prefix, Name, e.g. `/remove-kind bug` removes `kind/bug`
+    RemoveLabel(String, String),
+    // Title
+    Milestone(String),
+    RemoveMilestone,
+    // Logins, none means the commenter
+    Assign(Vec<String>),
+    Unassign(Vec<String>),
+    // Branch, e.g. `release-0.2`
+    CherryPick(String),
+    Close,
+    Reopen,
+    // Title
+    Retitle(String),
+}
+
+impl Command {
+    // from_invocation returns the commands of an invocation, e.g. `/kind bug
+    // documentation` adds two labels. Invocations that are not ours, e.g. for
+    // another bot, have none.
+    pub fn from_invocation(
+        invocation: &Invocation,
+        label_prefixes: &[String],
+    ) -> Result<Vec<Self>> {
+        let args = &invocation.args;
+        let words = || args.split_whitespace().map(|word| word.to_string());
+        let logins = || {
+            words()
+                .map(|login| login.trim_start_matches('@').to_string())
+                .collect()
+        };
+        let missing = |argument: &str, example: &str| Error::MissingArgument {
+            command: invocation.name.clone(),
+            argument: argument.to_string(),
+            example: example.to_string(),
+        };
+
+        let commands = match invocation.name.as_str() {
+            "milestone" if args.is_empty() => {
+                return Err(missing(
+                    "the title of a milestone",
+                    "/milestone Trustify 2.1.0",
+                ))
+            }
+            "milestone" => vec![Command::Milestone(args.clone())],
+            "remove-milestone" => vec![Command::RemoveMilestone],
+            "assign" => vec![Command::Assign(logins())],
+            "unassign" => vec![Command::Unassign(logins())],
+            "cherry-pick" if args.is_empty() => {
+                return Err(missing("a release branch", "/cherry-pick release-0.2"))
+            }
+            "cherry-pick" => words().map(Command::CherryPick).collect(),
+            "close" => vec![Command::Close],
+            "reopen" => vec![Command::Reopen],
+            "retitle" if args.is_empty() => {
+                return Err(missing("the new title", "/retitle :bug: Fix the bug"))
+            }
+            "retitle" => vec![Command::Retitle(args.clone())],
+            name => {
+                let (remove, prefix) = match name.strip_prefix("remove-") {
+                    Some(prefix) => (true, prefix),
+                    None => (false, name),
+                };
+                let Some(prefix) = label_prefixes
+                    .iter()
+                    .find(|p| p.eq_ignore_ascii_case(prefix))
+                else {
+                    return Ok(vec![]);
+                };
+                if args.is_empty() {
+                    return Err(missing("the name of a label", &format!("/{name} <name>")));
+                }
+
+                words()
+                    .map(|label| match remove {
+                        true => Command::RemoveLabel(prefix.clone(), label),
+                        false => Command::AddLabel(prefix.clone(), label),
+                    })
+                    .collect()
+            }
+        };
+
+        Ok(commands)
+    }
+
+    // name is used to look up who may run the command, e.g. `remove-kind`
+    pub fn name(&self) -> String {
+        match self {
+            Command::AddLabel(prefix, _) => prefix.clone(),
+            Command::RemoveLabel(prefix, _) => format!("remove-{prefix}"),
+            Command::Milestone(_) => "milestone".to_string(),
+            Command::RemoveMilestone => "remove-milestone".to_string(),
+            Command::Assign(_) => "assign".to_string(),
+            Command::Unassign(_) => "unassign".to_string(),
+            Command::CherryPick(_) => "cherry-pick".to_string(),
+            Command::Close => "close".to_string(),
+            Command::Reopen => "reopen".to_string(),
+            Command::Retitle(_) => "retitle".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Command::AddLabel(_, label) | Command::RemoveLabel(_, label) => {
+                write!(f, "/{} {}", self.name(), label)
+            }
+            Command::Milestone(title) => write!(f, "/milestone {}", title),
+            Command::Assign(logins) | Command::Unassign(logins) if !logins.is_empty() => {
+                let logins: Vec<String> = logins.iter().map(|l| format!("@{l}")).collect();
+                write!(f, "/{} {}", self.name(), logins.join(" "))
+            }
+            Command::CherryPick(branch) => write!(f, "/cherry-pick {}", branch),
+            Command::Retitle(title) => write!(f, "/retitle {}", title),
+            _ => write!(f, "/{}", self.name()),
+        }
+    }
+}
+
+// commands parses a comment into the commands it asks for, along with the
+// problems of the invalid ones.
+pub fn commands(body: &str, label_prefixes: &[String]) -> (Vec<Command>, Vec<Error>) {
+    let mut commands = vec![];
+    let mut problems = vec![];
+    for invocation in crate::parse::parse(body) {
+        match Command::from_invocation(&invocation, label_prefixes) {
+            Ok(found) => commands.extend(found),
+            Err(error) => problems.push(error),
+        }
+    }
+    (commands, problems)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        command::{commands, Command},
+        error::Error,
+    };
+
+    #[test]
+    fn parse() {
+        let prefixes = vec!["kind".to_string(), "priority".to_string()];
+        let body = "\
+/kind bug documentation
+/remove-priority backlog
+/lgtm
+/milestone Trustify 2.1.0
+/remove-milestone
+/assign @alice bob
+/unassign
+/cherry-pick release-0.2 release-0.3
+/retitle :bug: Fix the bug
+/close
+/reopen
+/remove-kind
+";
+        let (found, problems) = commands(body, &prefixes);
+        assert_eq!(
+            vec![
+                Command::AddLabel("kind".to_string(), "bug".to_string()),
+                Command::AddLabel("kind".to_string(), "documentation".to_string()),
+                Command::RemoveLabel("priority".to_string(), "backlog".to_string()),
+                Command::Milestone("Trustify 2.1.0".to_string()),
+                Command::RemoveMilestone,
+                Command::Assign(vec!["alice".to_string(), "bob".to_string()]),
+                Command::Unassign(vec![]),
+                Command::CherryPick("release-0.2".to_string()),
+                Command::CherryPick("release-0.3".to_string()),
+                Command::Retitle(":bug: Fix the bug".to_string()),
+                Command::Close,
+                Command::Reopen,
+            ],
+            found
+        );
+        assert_eq!(
+            vec![Error::MissingArgument {
+                command: "remove-kind".to_string(),
+                argument: "the name of a label".to_string(),
+                example: "/remove-kind <name>".to_string(),
+            }],
+            problems
+        );
+    }
+
+    #[test]
+    fn display() {
+        let display = |command: Command| command.to_string();
+        assert_eq!(
+            "/remove-kind bug",
+            display(Command::RemoveLabel("kind".to_string(), "bug".to_string()))
+        );
+        assert_eq!(
+            "/assign @alice @bob",
+            display(Command::Assign(vec![
+                "alice".to_string(),
+                "bob".to_string()
+            ]))
+        );
+        assert_eq!("/unassign", display(Command::Unassign(vec![])));
+        assert_eq!("/close", display(Command::Close));
+    }
+}
@@ -0,0 +1,11 @@
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub enum Error {
+    #[error("/{command} needs {argument}, e.g. {example}")]
+    MissingArgument {
+        command: String,
+        argument: String,
+        example: String,
+    },
+}
@@ -0,0 +1,3 @@
+pub mod command;
+pub mod error;
+pub mod parse;
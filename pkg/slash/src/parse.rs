@@ -0,0 +1,115 @@
+use regex::Regex;
+
+// Invocation is a line of a comment starting with a slash, e.g. `/kind bug`
+// has the name `kind` and the arguments `bug`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Invocation {
+    // Name is lowercase, without the slash
+    pub name: String,
+    pub args: String,
+}
+
+// parse finds the invocations of a comment. Commands must start their own
+// line, the ones in HTML comments and code blocks are ignored, e.g. when
+// quoting the documentation.
+pub fn parse(body: &str) -> Vec<Invocation> {
+    let comment_regex = Regex::new(r"(?s)<!--.*?(-->|$)").unwrap();
+    let command_regex = Regex::new(r"^/([A-Za-z][A-Za-z0-9-]*)(?:\s+(.*))?$").unwrap();
+
+    let body = comment_regex.replace_all(body, "");
+
+    let mut invocations = vec![];
+    let mut fence: Option<String> = None;
+    for line in body.lines() {
+        let line = line.trim_end();
+
+        // Fenced code blocks end with the same fence, or a longer one
+        let trimmed = line.trim_start();
+        let marker: String = match trimmed.chars().next() {
+            Some(c @ ('`' | '~')) => trimmed.chars().take_while(|m| *m == c).collect(),
+            _ => String::new(),
+        };
+        match &fence {
+            Some(open) if marker.starts_with(open.as_str()) => {
+                fence = None;
+                continue;
+            }
+            Some(_) => continue,
+            None if marker.len() >= 3 => {
+                fence = Some(marker);
+                continue;
+            }
+            None => {}
+        }
+
+        if let Some(captures) = command_regex.captures(line) {
+            invocations.push(Invocation {
+                name: captures[1].to_lowercase(),
+                args: captures
+                    .get(2)
+                    .map_or("", |args| args.as_str())
+                    .trim()
+                    .to_string(),
+            });
+        }
+    }
+
+    invocations
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse::{parse, Invocation};
+
+    fn invocation(name: &str, args: &str) -> Invocation {
+        Invocation {
+            name: name.to_string(),
+            args: args.to_string(),
+        }
+    }
+
+    #[test]
+    fn commands() {
+        let body = "\
+Thanks!
+/kind bug
+/Priority   important-soon  \r
+/close
+not a /command
+/usr/bin/env is not one either
+";
+        assert_eq!(
+            vec![
+                invocation("kind", "bug"),
+                invocation("priority", "important-soon"),
+                invocation("close", ""),
+            ],
+            parse(body)
+        );
+    }
+
+    #[test]
+    fn ignored() {
+        let body = "\
+<!-- /kind bug -->
+<!--
+/kind feature
+-->
+```
+/close
+```
+~~~~markdown
+/retitle Code
+~~~
+/assign
+~~~~
+    /kind documentation
+> /priority backlog
+`/reopen`
+/triage accepted
+<!-- unterminated
+/kind bug
+";
+        assert_eq!(vec![invocation("triage", "accepted")], parse(body));
+    }
+}
@@ -0,0 +1,120 @@
+use core::fmt;
+use std::str::FromStr;
+
+use regex::Regex;
+
+use crate::{
+    error::{Error, Result},
+    version::Version,
+};
+
+// Branch is a branch releases are made from, see VERSIONING.md.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Branch {
+    // Main only produces pre-releases of the next minor, `vX.Y.0-alpha.N`
+    Main,
+    // Release produces the stable releases of a minor, `vX.Y.Z`
+    Release { major: u64, minor: u64 },
+}
+
+impl Branch {
+    // check fails when the version can't be released from this branch
+    pub fn check(&self, version: &Version) -> Result<()> {
+        let reason = match self {
+            Branch::Main if !version.is_prerelease() || version.patch != 0 => {
+                "main only makes pre-releases of the next minor, vX.Y.0-alpha.N"
+            }
+            Branch::Release { .. } if version.is_prerelease() => {
+                "release branches only make stable releases, vX.Y.Z"
+            }
+            Branch::Release { major, minor }
+                if (version.major, version.minor) != (*major, *minor) =>
+            {
+                "release branches only make releases of their own minor"
+            }
+            _ => return Ok(()),
+        };
+
+        Err(Error::NotAllowed {
+            version: version.to_string(),
+            branch: self.to_string(),
+            reason: reason.to_string(),
+        })
+    }
+}
+
+impl FromStr for Branch {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        if value == "main" {
+            return Ok(Branch::Main);
+        }
+
+        let release_regex = Regex::new(r"^release-(0|[1-9]\d*)\.(0|[1-9]\d*)$").unwrap();
+        let captures = release_regex
+            .captures(value)
+            .ok_or_else(|| Error::InvalidBranch(value.to_string()))?;
+
+        let number = |index: usize| {
+            captures[index]
+                .parse::<u64>()
+                .map_err(|_| Error::InvalidBranch(value.to_string()))
+        };
+
+        Ok(Branch::Release {
+            major: number(1)?,
+            minor: number(2)?,
+        })
+    }
+}
+
+impl fmt::Display for Branch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Branch::Main => write!(f, "main"),
+            Branch::Release { major, minor } => write!(f, "release-{}.{}", major, minor),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{branch::Branch, error::Error, version::Version};
+
+    #[test]
+    fn parse() {
+        assert_eq!(Ok(Branch::Main), "main".parse());
+        assert_eq!(
+            Ok(Branch::Release {
+                major: 10,
+                minor: 0
+            }),
+            "release-10.0".parse()
+        );
+
+        for branch in ["master", "release-0.3.1", "release-01.3", "release/0.3"] {
+            assert_eq!(
+                Err(Error::InvalidBranch(branch.to_string())),
+                branch.parse::<Branch>()
+            );
+        }
+    }
+
+    #[test]
+    fn check() {
+        let allowed = |branch: &str, tag: &str| {
+            let branch: Branch = branch.parse().unwrap();
+            branch.check(&tag.parse::<Version>().unwrap()).is_ok()
+        };
+
+        assert!(allowed("main", "v2.0.0-alpha.1"));
+        assert!(!allowed("main", "v2.0.0"));
+        assert!(!allowed("main", "v2.0.1-alpha.1"));
+
+        assert!(allowed("release-2.3", "v2.3.0"));
+        assert!(allowed("release-2.3", "v2.3.1"));
+        assert!(!allowed("release-2.3", "v2.3.1-alpha.1"));
+        assert!(!allowed("release-2.3", "v2.4.0"));
+    }
+}
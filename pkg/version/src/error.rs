@@ -0,0 +1,17 @@
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub enum Error {
+    #[error("{0:?} is not a release tag, expected vX.Y.Z or vX.Y.0-alpha.N, see VERSIONING.md")]
+    InvalidTag(String),
+
+    #[error("{0:?} is not a release branch, expected main or release-X.Y, see VERSIONING.md")]
+    InvalidBranch(String),
+
+    #[error("{version} can't be released from {branch}, {reason}")]
+    NotAllowed {
+        version: String,
+        branch: String,
+        reason: String,
+    },
+}
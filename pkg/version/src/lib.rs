@@ -0,0 +1,4 @@
+pub mod branch;
+pub mod error;
+pub mod next;
+pub mod version;
@@ -0,0 +1,271 @@
+use pr::prefix::PRType;
+
+use crate::{
+    branch::Branch,
+    error::{Error, Result},
+    version::Version,
+};
+
+// Bump is how far a set of changes moves the version.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Bump {
+    Patch,
+    Minor,
+    // Breaking moves the minor while the major is 0, the major after 1.0
+    Breaking,
+}
+
+impl Bump {
+    // from_type returns None for changes that don't move the version on their
+    // own, e.g. docs or infra.
+    pub fn from_type(pr_type: &PRType) -> Option<Bump> {
+        match pr_type {
+            PRType::Breaking(_) => Some(Bump::Breaking),
+            PRType::Feature(_) => Some(Bump::Minor),
+            PRType::BugFix(_) => Some(Bump::Patch),
+            PRType::Docs(_) | PRType::Infra(_) | PRType::NoNote(_) => None,
+        }
+    }
+
+    // highest returns the largest bump of the changes
+    pub fn highest<'a>(pr_types: impl IntoIterator<Item = &'a PRType>) -> Option<Bump> {
+        pr_types.into_iter().filter_map(Bump::from_type).max()
+    }
+
+    pub fn apply(&self, version: &Version) -> Version {
+        match self {
+            Bump::Breaking if version.major > 0 => Version::new(version.major + 1, 0, 0),
+            Bump::Breaking | Bump::Minor => Version::new(version.major, version.minor + 1, 0),
+            Bump::Patch => Version::new(version.major, version.minor, version.patch + 1),
+        }
+    }
+}
+
+// next computes the version to release from the branch, given the existing
+// tags of the repository and the highest bump since the last one.
+//
+// Main makes the next alpha of the upcoming minor, or major, release. A
+// release branch makes `vX.Y.0` first and patch releases after, changes that
+// need more than a patch are refused there.
+pub fn next(branch: &Branch, tags: &[Version], bump: Option<Bump>) -> Result<Version> {
+    match branch {
+        Branch::Main => {
+            let stable = tags
+                .iter()
+                .filter(|v| !v.is_prerelease())
+                .max()
+                .cloned()
+                .unwrap_or(Version::new(0, 0, 0));
+
+            let mut target = bump.unwrap_or(Bump::Minor).max(Bump::Minor).apply(&stable);
+
+            // Pre-releases already made for a later release keep their target
+            if let Some(pending) = tags
+                .iter()
+                .filter(|v| v.is_prerelease() && v.release() > stable)
+                .map(|v| v.release())
+                .max()
+            {
+                target = target.max(pending);
+            }
+
+            let alpha = tags
+                .iter()
+                .filter(|v| v.is_prerelease() && v.release() == target)
+                .filter_map(|v| v.alpha)
+                .max()
+                .map_or(1, |alpha| alpha + 1);
+
+            Ok(Version {
+                alpha: Some(alpha),
+                ..target
+            })
+        }
+        Branch::Release { major, minor } => {
+            let latest = tags
+                .iter()
+                .filter(|v| !v.is_prerelease() && (v.major, v.minor) == (*major, *minor))
+                .max();
+
+            let version = match latest {
+                Some(latest) => Bump::Patch.apply(latest),
+                None => Version::new(*major, *minor, 0),
+            };
+
+            if bump > Some(Bump::Patch) {
+                return Err(Error::NotAllowed {
+                    version: version.to_string(),
+                    branch: branch.to_string(),
+                    reason: "release branches only take bug fixes".to_string(),
+                });
+            }
+
+            Ok(version)
+        }
+    }
+}
+
+// previous returns the tag the next release of the branch follows, the
+// changes since then decide the bump.
+//
+// A release branch without releases yet follows the last pre-release of its
+// minor made from main.
+pub fn previous<'a>(branch: &Branch, tags: &'a [Version]) -> Option<&'a Version> {
+    match branch {
+        Branch::Main => tags.iter().max(),
+        Branch::Release { major, minor } => {
+            let own = |v: &&Version| (v.major, v.minor) == (*major, *minor);
+            tags.iter()
+                .filter(own)
+                .filter(|v| !v.is_prerelease())
+                .max()
+                .or_else(|| tags.iter().filter(own).filter(|v| v.patch == 0).max())
+        }
+    }
+}
+
+// previous_of returns the tag the release notes of the version start from. A
+// pre-release follows the tag before it, a release the stable release before
+// it so a `vX.Y.0` covers everything its pre-releases did.
+pub fn previous_of<'a>(version: &Version, tags: &'a [Version]) -> Option<&'a Version> {
+    tags.iter()
+        .filter(|v| *v < version)
+        .filter(|v| version.is_prerelease() || !v.is_prerelease())
+        .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use pr::prefix::PRType;
+
+    use crate::{
+        branch::Branch,
+        next::{next, previous, previous_of, Bump},
+        version::Version,
+    };
+
+    struct TestCase {
+        pub branch: &'static str,
+        pub tags: Vec<&'static str>,
+        pub bump: Option<Bump>,
+        pub expected: Option<&'static str>,
+    }
+
+    #[test]
+    fn highest() {
+        let pr_types = [
+            PRType::Docs("docs".to_string()),
+            PRType::BugFix("fix".to_string()),
+            PRType::Feature("feature".to_string()),
+        ];
+        assert_eq!(Some(Bump::Minor), Bump::highest(&pr_types));
+        assert_eq!(None, Bump::highest(&pr_types[..1]));
+    }
+
+    #[test]
+    fn previous_version() {
+        let tags: Vec<Version> = [
+            "v0.2.0",
+            "v0.2.1",
+            "v0.3.0-alpha.1",
+            "v0.3.0-alpha.2",
+            "v0.4.0-alpha.1",
+        ]
+        .iter()
+        .map(|t| t.parse().unwrap())
+        .collect();
+
+        let previous =
+            |branch: &str| previous(&branch.parse().unwrap(), &tags).map(|v| v.to_string());
+        assert_eq!(Some("v0.4.0-alpha.1".to_string()), previous("main"));
+        assert_eq!(Some("v0.2.1".to_string()), previous("release-0.2"));
+        assert_eq!(Some("v0.3.0-alpha.2".to_string()), previous("release-0.3"));
+        assert_eq!(None, previous("release-0.5"));
+
+        let previous_of =
+            |tag: &str| previous_of(&tag.parse().unwrap(), &tags).map(|v| v.to_string());
+        assert_eq!(
+            Some("v0.3.0-alpha.1".to_string()),
+            previous_of("v0.3.0-alpha.2")
+        );
+        assert_eq!(Some("v0.2.1".to_string()), previous_of("v0.3.0"));
+        assert_eq!(Some("v0.2.1".to_string()), previous_of("v0.2.2"));
+        assert_eq!(None, previous_of("v0.2.0"));
+    }
+
+    #[test]
+    fn next_version() {
+        let test_cases = vec![
+            TestCase {
+                branch: "main",
+                tags: vec![],
+                bump: None,
+                expected: Some("v0.1.0-alpha.1"),
+            },
+            TestCase {
+                branch: "main",
+                tags: vec!["v0.2.0", "v0.3.0-alpha.1", "v0.3.0-alpha.2"],
+                bump: Some(Bump::Patch),
+                expected: Some("v0.3.0-alpha.3"),
+            },
+            TestCase {
+                branch: "main",
+                tags: vec!["v0.2.0", "v0.3.0-alpha.1"],
+                bump: Some(Bump::Breaking),
+                expected: Some("v0.3.0-alpha.2"),
+            },
+            TestCase {
+                branch: "main",
+                tags: vec!["v1.2.0", "v1.2.1", "v1.3.0-alpha.1"],
+                bump: Some(Bump::Breaking),
+                expected: Some("v2.0.0-alpha.1"),
+            },
+            TestCase {
+                branch: "main",
+                tags: vec!["v1.2.0", "v2.0.0-alpha.1"],
+                bump: Some(Bump::Minor),
+                expected: Some("v2.0.0-alpha.2"),
+            },
+            TestCase {
+                branch: "main",
+                tags: vec!["v0.3.0-alpha.2", "v0.3.0"],
+                bump: Some(Bump::Minor),
+                expected: Some("v0.4.0-alpha.1"),
+            },
+            TestCase {
+                branch: "release-0.3",
+                tags: vec!["v0.3.0-alpha.2", "v0.4.0-alpha.1"],
+                bump: None,
+                expected: Some("v0.3.0"),
+            },
+            TestCase {
+                branch: "release-0.3",
+                tags: vec!["v0.3.0", "v0.3.1", "v0.4.0-alpha.1"],
+                bump: Some(Bump::Patch),
+                expected: Some("v0.3.2"),
+            },
+            TestCase {
+                branch: "release-0.3",
+                tags: vec!["v0.3.0"],
+                bump: Some(Bump::Minor),
+                expected: None,
+            },
+        ];
+
+        for tc in test_cases {
+            let branch: Branch = tc.branch.parse().unwrap();
+            let tags: Vec<Version> = tc.tags.iter().map(|t| t.parse().unwrap()).collect();
+
+            let version = next(&branch, &tags, tc.bump);
+            assert_eq!(
+                tc.expected,
+                version.as_ref().ok().map(|v| v.to_string()).as_deref(),
+                "{} {:?} {:?}: {:?}",
+                tc.branch,
+                tc.tags,
+                tc.bump,
+                version
+            );
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use core::fmt;
+use std::{cmp::Ordering, str::FromStr};
+
+use regex::Regex;
+
+use crate::error::{Error, Result};
+
+// Version is a release tag as described in VERSIONING.md, either `vX.Y.Z` or
+// the pre-release `vX.Y.Z-alpha.N` made from main.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    pub alpha: Option<u64>,
+}
+
+impl Version {
+    pub fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Version {
+            major,
+            minor,
+            patch,
+            alpha: None,
+        }
+    }
+
+    pub fn is_prerelease(&self) -> bool {
+        self.alpha.is_some()
+    }
+
+    // is_dotzero reports the first release of a minor, e.g. v2.3.0
+    pub fn is_dotzero(&self) -> bool {
+        self.patch == 0 && self.alpha.is_none()
+    }
+
+    // release returns the version this one is a pre-release of
+    pub fn release(&self) -> Version {
+        Version::new(self.major, self.minor, self.patch)
+    }
+
+    // xy returns the minor version, as used by the `release-X.Y` branches
+    pub fn xy(&self) -> String {
+        format!("{}.{}", self.major, self.minor)
+    }
+}
+
+impl FromStr for Version {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        let tag_regex =
+            Regex::new(r"^v(0|[1-9]\d*)\.(0|[1-9]\d*)\.(0|[1-9]\d*)(?:-alpha\.(0|[1-9]\d*))?$")
+                .unwrap();
+
+        let captures = tag_regex
+            .captures(value)
+            .ok_or_else(|| Error::InvalidTag(value.to_string()))?;
+
+        // The regex only matches digits, parsing fails on overflow alone
+        let number = |index: usize| -> Result<Option<u64>> {
+            captures
+                .get(index)
+                .map(|m| m.as_str().parse::<u64>())
+                .transpose()
+                .map_err(|_| Error::InvalidTag(value.to_string()))
+        };
+
+        Ok(Version {
+            major: number(1)?.unwrap_or_default(),
+            minor: number(2)?.unwrap_or_default(),
+            patch: number(3)?.unwrap_or_default(),
+            alpha: number(4)?,
+        })
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "v{}.{}.{}", self.major, self.minor, self.patch)?;
+        if let Some(alpha) = self.alpha {
+            write!(f, "-alpha.{}", alpha)?;
+        }
+        Ok(())
+    }
+}
+
+// Versions follow the Semantic Versioning precedence, a pre-release comes
+// before the release it leads to.
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| match (self.alpha, other.alpha) {
+                (None, None) => Ordering::Equal,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(_), None) => Ordering::Less,
+                (Some(a), Some(b)) => a.cmp(&b),
+            })
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{error::Error, version::Version};
+
+    #[test]
+    fn parse() {
+        assert_eq!(Ok(Version::new(1, 2, 3)), "v1.2.3".parse());
+        assert_eq!(
+            Ok(Version {
+                alpha: Some(2),
+                ..Version::new(2, 0, 0)
+            }),
+            "v2.0.0-alpha.2".parse()
+        );
+
+        for tag in [
+            "1.2.3",
+            "v1.2",
+            "v01.2.3",
+            "v1.2.3-beta.1",
+            "v1.2.3-alpha",
+            "v1.2.3+build",
+        ] {
+            assert_eq!(
+                Err(Error::InvalidTag(tag.to_string())),
+                tag.parse::<Version>()
+            );
+        }
+    }
+
+    #[test]
+    fn display() {
+        for tag in ["v0.3.1", "v2.0.0-alpha.10"] {
+            assert_eq!(tag, tag.parse::<Version>().unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn order() {
+        let mut versions: Vec<Version> = [
+            "v1.0.0",
+            "v0.10.0",
+            "v1.0.0-alpha.10",
+            "v0.9.1",
+            "v1.0.0-alpha.2",
+        ]
+        .iter()
+        .map(|tag| tag.parse().unwrap())
+        .collect();
+        versions.sort();
+
+        assert_eq!(
+            vec![
+                "v0.9.1",
+                "v0.10.0",
+                "v1.0.0-alpha.2",
+                "v1.0.0-alpha.10",
+                "v1.0.0"
+            ],
+            versions.iter().map(|v| v.to_string()).collect::<Vec<_>>()
+        );
+    }
+}